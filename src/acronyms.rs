@@ -0,0 +1,47 @@
+use std::slice::Iter;
+
+/// The acronym definitions for a document, rendered as an `acronym`
+/// environment from the `acronym` package. The package is imported
+/// automatically when the document is rendered.
+///
+/// Acronyms are then used in the text with `ParagraphElement::Acro`, which
+/// spells out the long form the first time.
+///
+/// ```rust
+/// use latex::AcronymList;
+///
+/// let mut acronyms = AcronymList::new();
+/// acronyms.push("API", "API", "Application Programming Interface");
+/// ```
+///
+/// Which would be rendered as:
+///
+/// ```tex
+/// \begin{acronym}
+/// \acro{API}[API]{Application Programming Interface}
+/// \end{acronym}
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AcronymList {
+    items: Vec<(String, String, String)>,
+}
+
+impl AcronymList {
+    /// Create an empty list of acronyms.
+    pub fn new() -> AcronymList {
+        Default::default()
+    }
+
+    /// Define an acronym, made up of the key used to refer to it, its short
+    /// form and its long form.
+    pub fn push(&mut self, key: &str, short: &str, long: &str) -> &mut Self {
+        self.items
+            .push((key.to_string(), short.to_string(), long.to_string()));
+        self
+    }
+
+    /// Iterate over each `(key, short, long)` definition.
+    pub fn iter(&self) -> Iter<'_, (String, String, String)> {
+        self.items.iter()
+    }
+}
@@ -0,0 +1,478 @@
+use std::fmt::{self, Display, Formatter};
+use std::slice::Iter;
+
+use failure::Error;
+
+use visitor::Diagnostic;
+
+/// The styles which come with a standard BibTeX installation, plus the common
+/// `natbib` ones. The styles in `AUTHOR_YEAR_STYLES` are known too.
+const KNOWN_STYLES: &[&str] = &[
+    "plain",
+    "unsrt",
+    "alpha",
+    "abbrv",
+    "acm",
+    "apalike",
+    "ieeetr",
+    "siam",
+    "amsplain",
+    "amsalpha",
+    "plainnat",
+    "unsrtnat",
+    "abbrvnat",
+    "IEEEtran",
+    "ACM-Reference-Format",
+];
+
+/// Well-known styles which label citations by author and year rather than by
+/// number.
+const AUTHOR_YEAR_STYLES: &[&str] = &[
+    "apalike", "plainnat", "unsrtnat", "abbrvnat", "agsm", "dcu", "kluwer", "chicago", "apa",
+];
+
+/// How a bibliography style labels its citations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CiteScheme {
+    /// A number or short code in brackets (e.g. "[1]" or "[Knu84]"), which
+    /// suits a plain `\cite`.
+    Numeric,
+    /// The author and year (e.g. "Knuth (1984)"), which suits `natbib`'s
+    /// `\citet` and `\citep`.
+    AuthorYear,
+}
+
+/// A BibTeX bibliography style, as passed to `\bibliographystyle`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BibStyle {
+    /// Entries sorted alphabetically and labelled numerically.
+    Plain,
+    /// Entries in order of first citation and labelled numerically.
+    Unsrt,
+    /// Like `Plain`, but labelled with an abbreviation of the author and year.
+    Alpha,
+    /// Like `Plain`, but with abbreviated first names and journal names.
+    Abbrv,
+    /// The style used by IEEE journals and conferences (`IEEEtran`).
+    IeeeTran,
+    /// The style used by ACM publications (`ACM-Reference-Format`).
+    AcmReference,
+    /// Any other style, referred to by name.
+    Custom(String),
+}
+
+impl BibStyle {
+    /// Is this the name of a well-known BibTeX style?
+    pub fn is_known(name: &str) -> bool {
+        KNOWN_STYLES.contains(&name) || AUTHOR_YEAR_STYLES.contains(&name)
+    }
+
+    /// Work out whether this style labels citations numerically or by
+    /// author and year.
+    ///
+    /// Unknown `Custom` styles are assumed to be numeric unless their name
+    /// mentions author-year labelling, since there's no way to tell for sure
+    /// without running BibTeX.
+    ///
+    /// ```rust
+    /// use latex::{BibStyle, CiteScheme};
+    ///
+    /// assert_eq!(BibStyle::Plain.scheme(), CiteScheme::Numeric);
+    /// assert_eq!(BibStyle::Custom("apalike".to_string()).scheme(), CiteScheme::AuthorYear);
+    /// ```
+    pub fn scheme(&self) -> CiteScheme {
+        match *self {
+            BibStyle::Custom(ref name)
+                if AUTHOR_YEAR_STYLES.contains(&name.as_str()) || name.contains("authoryear") =>
+            {
+                CiteScheme::AuthorYear
+            }
+            _ => CiteScheme::Numeric,
+        }
+    }
+
+    /// The name of the equivalent `biblatex` style, for the presets which
+    /// have one.
+    ///
+    /// ```rust
+    /// use latex::BibStyle;
+    ///
+    /// assert_eq!(BibStyle::IeeeTran.biblatex_style(), Some("ieee"));
+    /// assert_eq!(BibStyle::Custom("siam".to_string()).biblatex_style(), None);
+    /// ```
+    pub fn biblatex_style(&self) -> Option<&'static str> {
+        match *self {
+            BibStyle::Plain => Some("numeric"),
+            BibStyle::Alpha => Some("alphabetic"),
+            BibStyle::IeeeTran => Some("ieee"),
+            BibStyle::AcmReference => Some("acmnumeric"),
+            _ => None,
+        }
+    }
+
+    /// Check a style for likely mistakes, such as a misspelled `Custom` name.
+    ///
+    /// Unknown styles aren't necessarily wrong (you may have installed your
+    /// own), so this only produces a warning.
+    pub fn validate(&self) -> Option<Diagnostic> {
+        match *self {
+            BibStyle::Custom(ref name) if !BibStyle::is_known(name) => {
+                Some(Diagnostic::UnknownBibStyle(name.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for BibStyle {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            BibStyle::Plain => write!(f, "plain"),
+            BibStyle::Unsrt => write!(f, "unsrt"),
+            BibStyle::Alpha => write!(f, "alpha"),
+            BibStyle::Abbrv => write!(f, "abbrv"),
+            BibStyle::IeeeTran => write!(f, "IEEEtran"),
+            BibStyle::AcmReference => write!(f, "ACM-Reference-Format"),
+            BibStyle::Custom(ref s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// The program used to process a document's bibliography.
+///
+/// The two backends are configured in incompatible ways: BibTeX takes its
+/// style from a `\bibliographystyle` command, while biblatex is loaded as a
+/// package and takes the style as a package option. Setting a backend on a
+/// `Document` lets `validate()` catch a mix of the two.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BibBackend {
+    /// Classic BibTeX, using `\bibliographystyle` and `\bibliography`.
+    Bibtex,
+    /// The `biblatex` package (usually with `biber`).
+    Biblatex,
+}
+
+/// The package options used to set up `biblatex` with
+/// `Preamble::biblatex()`. Any options left as `None` use biblatex's own
+/// defaults.
+///
+/// ```rust
+/// use latex::BiblatexOptions;
+///
+/// let options = BiblatexOptions {
+///     backend: Some("biber".to_string()),
+///     style: Some("authoryear".to_string()),
+///     ..Default::default()
+/// };
+/// assert_eq!(options.to_string(), "backend=biber,style=authoryear");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BiblatexOptions {
+    /// The program which processes the bibliography (e.g. `biber`, `bibtex`).
+    pub backend: Option<String>,
+    /// The citation and bibliography style (e.g. `authoryear`, `numeric`).
+    pub style: Option<String>,
+    /// The order entries are sorted in (e.g. `nyt` for name, year, title).
+    pub sorting: Option<String>,
+}
+
+impl Display for BiblatexOptions {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let options = [
+            ("backend", &self.backend),
+            ("style", &self.style),
+            ("sorting", &self.sorting),
+        ];
+        let set: Vec<_> = options
+            .iter()
+            .filter_map(|&(key, value)| value.as_ref().map(|v| format!("{}={}", key, v)))
+            .collect();
+
+        write!(f, "{}", set.join(","))
+    }
+}
+
+/// Options for biblatex's `\printbibliography` command.
+///
+/// ```rust
+/// use latex::PrintBibOptions;
+///
+/// let options = PrintBibOptions {
+///     title: Some("References".to_string()),
+///     heading: Some("bibintoc".to_string()),
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     options.command(),
+///     "\\printbibliography[title={References},heading=bibintoc]"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PrintBibOptions {
+    /// The heading's text, replacing the default "References"/"Bibliography".
+    pub title: Option<String>,
+    /// The kind of heading (e.g. `bibintoc` to list it in the table of
+    /// contents, or `none`).
+    pub heading: Option<String>,
+    /// Only print entries with this keyword.
+    pub keyword: Option<String>,
+}
+
+impl PrintBibOptions {
+    /// The `\printbibliography` command using these options.
+    pub fn command(&self) -> String {
+        let options = self.to_string();
+
+        if options.is_empty() {
+            r"\printbibliography".to_string()
+        } else {
+            format!(r"\printbibliography[{}]", options)
+        }
+    }
+}
+
+impl Display for PrintBibOptions {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut set = Vec::new();
+        if let Some(ref title) = self.title {
+            // braces stop commas in the title from ending the option
+            set.push(format!("title={{{}}}", title));
+        }
+        if let Some(ref heading) = self.heading {
+            set.push(format!("heading={}", heading));
+        }
+        if let Some(ref keyword) = self.keyword {
+            set.push(format!("keyword={}", keyword));
+        }
+
+        write!(f, "{}", set.join(","))
+    }
+}
+
+/// Generate the `\bibliographystyle` and `\bibliography` commands used by the
+/// classic BibTeX workflow.
+///
+/// Any `.bib` extension on `file_stem` is stripped, because BibTeX adds it
+/// itself.
+///
+/// # Examples
+///
+/// ```rust
+/// use latex::{classic_commands, BibStyle, Element};
+///
+/// let commands = classic_commands(&BibStyle::Plain, "main.bib");
+/// assert_eq!(commands, "\\bibliographystyle{plain}\n\\bibliography{main}");
+///
+/// // The commands can then be added to the end of a document
+/// let element = Element::UserDefined(commands);
+/// ```
+pub fn classic_commands(style: &BibStyle, file_stem: &str) -> String {
+    classic_commands_multi(style, &[file_stem])
+}
+
+/// Like `classic_commands()`, but reading references from several `.bib`
+/// files.
+///
+/// ```rust
+/// use latex::{classic_commands_multi, BibStyle};
+///
+/// let commands = classic_commands_multi(&BibStyle::Alpha, &["papers", "books.bib"]);
+/// assert_eq!(commands, "\\bibliographystyle{alpha}\n\\bibliography{papers,books}");
+/// ```
+pub fn classic_commands_multi(style: &BibStyle, file_stems: &[&str]) -> String {
+    let stems: Vec<_> = file_stems
+        .iter()
+        .map(|stem| stem.strip_suffix(".bib").unwrap_or(stem))
+        .collect();
+
+    format!(
+        "\\bibliographystyle{{{}}}\n\\bibliography{{{}}}",
+        style,
+        stems.join(",")
+    )
+}
+
+/// Characters which BibTeX (or LaTeX, when the key is cited) can't cope
+/// with in a citation key, on top of whitespace.
+const INVALID_KEY_CHARACTERS: &[char] =
+    &[',', '{', '}', '%', '#', '~', '\\', '"', '\'', '(', ')', '='];
+
+/// Check that a citation key can be used with BibTeX, which doesn't allow
+/// whitespace, commas, braces or a few other special characters in keys.
+pub fn validate_key(key: &str) -> Result<(), Error> {
+    if key.is_empty() {
+        bail!("Citation keys can't be empty");
+    }
+
+    if let Some(c) = key
+        .chars()
+        .find(|&c| c.is_whitespace() || INVALID_KEY_CHARACTERS.contains(&c))
+    {
+        bail!("The citation key \"{}\" can't contain {:?}", key, c);
+    }
+
+    Ok(())
+}
+
+/// A bibliography written out by hand in a `thebibliography` environment,
+/// for documents which don't use BibTeX at all.
+///
+/// ```rust
+/// use latex::ManualBibliography;
+///
+/// let mut bib = ManualBibliography::new();
+/// bib.push("knuth84", r"D. E. Knuth, \emph{The \TeX book}, 1984.");
+/// ```
+///
+/// Which would be rendered as:
+///
+/// ```tex
+/// \begin{thebibliography}{9}
+/// \bibitem{knuth84} D. E. Knuth, \emph{The \TeX book}, 1984.
+/// \end{thebibliography}
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ManualBibliography {
+    items: Vec<(String, String)>,
+}
+
+impl ManualBibliography {
+    /// Create an empty bibliography.
+    pub fn new() -> ManualBibliography {
+        Default::default()
+    }
+
+    /// Add an entry, made up of the key used to `\cite` it and its already
+    /// formatted text.
+    pub fn push(&mut self, key: &str, text: &str) -> &mut Self {
+        self.items.push((key.to_string(), text.to_string()));
+        self
+    }
+
+    /// The widest label LaTeX should leave room for. The entries are
+    /// numbered, so this is a number with as many digits as the entry count.
+    pub fn widest_label(&self) -> String {
+        "9".repeat(self.items.len().to_string().len())
+    }
+
+    /// Iterate over each `(key, text)` entry.
+    pub fn iter(&self) -> Iter<'_, (String, String)> {
+        self.items.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn titled_bibliography_in_the_toc() {
+        let options = PrintBibOptions {
+            title: Some("Works Cited".to_string()),
+            heading: Some("bibintoc".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            options.command(),
+            r"\printbibliography[title={Works Cited},heading=bibintoc]"
+        );
+        assert_eq!(PrintBibOptions::default().command(), r"\printbibliography");
+    }
+
+    #[test]
+    fn misspelled_styles_are_unknown() {
+        assert!(BibStyle::is_known("plain"));
+        assert!(!BibStyle::is_known("palin"));
+
+        assert_eq!(BibStyle::Custom("plain".to_string()).validate(), None);
+        assert_eq!(
+            BibStyle::Custom("palin".to_string()).validate(),
+            Some(Diagnostic::UnknownBibStyle("palin".to_string()))
+        );
+
+        // author-year styles are known even if they aren't in KNOWN_STYLES
+        assert!(BibStyle::is_known("agsm"));
+        assert_eq!(BibStyle::Custom("apa".to_string()).validate(), None);
+    }
+
+    #[test]
+    fn numeric_and_author_year_schemes() {
+        assert_eq!(BibStyle::Plain.scheme(), CiteScheme::Numeric);
+        assert_eq!(BibStyle::Alpha.scheme(), CiteScheme::Numeric);
+        assert_eq!(
+            BibStyle::Custom("plain".to_string()).scheme(),
+            CiteScheme::Numeric
+        );
+        assert_eq!(
+            BibStyle::Custom("apalike".to_string()).scheme(),
+            CiteScheme::AuthorYear
+        );
+        assert_eq!(
+            BibStyle::Custom("plainnat".to_string()).scheme(),
+            CiteScheme::AuthorYear
+        );
+    }
+
+    #[test]
+    fn keys_with_spaces_are_rejected() {
+        assert!(validate_key("a key").is_err());
+        assert!(validate_key("a,key").is_err());
+        assert!(validate_key("").is_err());
+        for key in &["50%", "a#b", "a~b", r"a\b", "a\"b", "o'neil", "a(1)", "a=b"] {
+            assert!(validate_key(key).is_err(), "{} should be rejected", key);
+        }
+        assert!(validate_key("akey2020").is_ok());
+    }
+
+    #[test]
+    fn widest_label_grows_with_the_entry_count() {
+        let mut bib = ManualBibliography::new();
+        bib.push("a", "A.");
+        assert_eq!(bib.widest_label(), "9");
+
+        for i in 0..10 {
+            bib.push(&i.to_string(), "More.");
+        }
+        assert_eq!(bib.widest_label(), "99");
+    }
+
+    #[test]
+    fn classic_bibtex_commands() {
+        let should_be = "\\bibliographystyle{unsrt}\n\\bibliography{references}";
+
+        assert_eq!(classic_commands(&BibStyle::Unsrt, "references"), should_be);
+        assert_eq!(
+            classic_commands(&BibStyle::Unsrt, "references.bib"),
+            should_be
+        );
+    }
+
+    #[test]
+    fn conference_presets() {
+        assert_eq!(
+            classic_commands(&BibStyle::IeeeTran, "refs"),
+            "\\bibliographystyle{IEEEtran}\n\\bibliography{refs}"
+        );
+        assert_eq!(
+            classic_commands(&BibStyle::AcmReference, "refs"),
+            "\\bibliographystyle{ACM-Reference-Format}\n\\bibliography{refs}"
+        );
+        assert_eq!(BibStyle::IeeeTran.biblatex_style(), Some("ieee"));
+        assert_eq!(BibStyle::AcmReference.biblatex_style(), Some("acmnumeric"));
+        assert!(BibStyle::is_known("IEEEtran"));
+    }
+
+    #[test]
+    fn classic_bibtex_commands_with_two_files() {
+        assert_eq!(
+            classic_commands_multi(&BibStyle::Plain, &["refs1", "refs2.bib"]),
+            "\\bibliographystyle{plain}\n\\bibliography{refs1,refs2}"
+        );
+        // only one extension is removed
+        assert_eq!(
+            classic_commands_multi(&BibStyle::Plain, &["refs.bib.bib"]),
+            "\\bibliographystyle{plain}\n\\bibliography{refs.bib}"
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Document-wide settings for code highlighted with the `minted` package,
+/// applied with `Preamble::minted_setup()`.
+///
+/// `minted` runs Pygments to do the highlighting, so documents which use it
+/// need to be compiled with `-shell-escape`.
+///
+/// ```rust
+/// use latex::MintedOptions;
+///
+/// let options = MintedOptions {
+///     style: Some("friendly".to_string()),
+///     linenos: true,
+///     ..Default::default()
+/// };
+/// assert_eq!(options.to_string(), "style=friendly,linenos");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MintedOptions {
+    /// The Pygments style used for highlighting (e.g. `friendly`).
+    pub style: Option<String>,
+    /// Where `minted` keeps the files it caches between runs. This is passed
+    /// as a package option, so it isn't part of the `\setminted` options
+    /// this type displays as.
+    pub cachedir: Option<String>,
+    /// Number the lines of each listing.
+    pub linenos: bool,
+}
+
+impl Display for MintedOptions {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut set = Vec::new();
+        if let Some(ref style) = self.style {
+            set.push(format!("style={}", style));
+        }
+        if self.linenos {
+            set.push("linenos".to_string());
+        }
+
+        write!(f, "{}", set.join(","))
+    }
+}
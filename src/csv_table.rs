@@ -0,0 +1,54 @@
+use std::path::{Path, PathBuf};
+use std::slice::Iter;
+
+/// A table whose rows are read from a CSV file when the document is
+/// compiled, using `\csvreader` from the `csvsimple` package.
+///
+/// The file's first line is treated as a header and skipped, and each of its
+/// columns is shown in the order they appear in the file.
+///
+/// # Examples
+///
+/// ```rust
+/// use latex::CsvTable;
+///
+/// let mut table = CsvTable::new("results.csv");
+/// table.column("Name").column("Score");
+/// ```
+///
+/// Which would be rendered as:
+///
+/// ```tex
+/// \csvreader[tabular=ll,table head=Name & Score\\]{results.csv}{}{\csvcoli & \csvcolii}
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CsvTable {
+    path: PathBuf,
+    columns: Vec<String>,
+}
+
+impl CsvTable {
+    /// Create a table which reads its rows from the provided file.
+    pub fn new<P: Into<PathBuf>>(path: P) -> CsvTable {
+        CsvTable {
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Add a column, with the heading it should be given in the table.
+    pub fn column(&mut self, heading: &str) -> &mut Self {
+        self.columns.push(heading.to_string());
+        self
+    }
+
+    /// The CSV file the rows are read from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Iterate over the column headings.
+    pub fn iter(&self) -> Iter<'_, String> {
+        self.columns.iter()
+    }
+}
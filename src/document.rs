@@ -1,11 +1,30 @@
+use std::collections::HashMap;
 use std::fmt::{self, Display, Formatter};
-use std::ops::Deref;
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::{Deref, RangeInclusive};
+use std::path::PathBuf;
 use std::slice::Iter;
 
-use equations::Align;
+use failure::Error;
+
+use acronyms::AcronymList;
+use bibliography::{BibBackend, BiblatexOptions, ManualBibliography};
+use code::MintedOptions;
+use csv_table::CsvTable;
+use equations::{Align, Equation, LongEquation};
+use layout::{
+    AdjustBox, Aligned, ColorBox, Landscape, LineSpacing, NumberStyle, PageStyle, TocStyle,
+    WrapFigure,
+};
 use lists::List;
-use paragraph::Paragraph;
+use paragraph::{Paragraph, ParagraphElement};
+use plot::Plot;
+use proof::Proof;
+use references::HyperSetup;
 use section::Section;
+use template::Template;
+use visitor;
 
 /// The root Document node.
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -14,8 +33,32 @@ pub struct Document {
     pub class: DocumentClass,
     /// The `Document`'s preamble.
     pub preamble: Preamble,
+    /// Render footnotes as endnotes using the `endnotes` package. The notes
+    /// are printed wherever an `Element::PrintEndnotes` is placed.
+    pub endnotes: bool,
+    /// The bibliography backend this document is written for, which
+    /// `validate()` uses to reject commands meant for the other one.
+    pub bib_backend: Option<BibBackend>,
+    /// Have `validate()` warn about labels which don't start with the usual
+    /// prefix for the kind of thing they label (`sec:`, `eq:`, and so on).
+    pub check_label_prefixes: bool,
+    /// Load the lightweight `url` package for `\url{}` instead of pulling in
+    /// `hyperref`, for templates which don't allow hyperref. Nothing else
+    /// loads `hyperref` either, and `validate()` reports any commands which
+    /// need it.
+    pub url_package: bool,
+    /// Start each top-level `Section` on a new page by putting a
+    /// `\clearpage` in front of it.
+    pub section_page_break: bool,
+    /// Number the lines of the document with the `lineno` package (e.g. for
+    /// a copy sent out for review).
+    pub line_numbers: bool,
+    /// The engine the document will be compiled with, which `validate()`
+    /// uses to catch packages that only work with some engines.
+    pub engine: Option<Engine>,
     /// The various elements inside this `Document`.
     elements: Vec<Element>,
+    mode: Option<DocumentMode>,
 }
 
 impl Document {
@@ -40,11 +83,244 @@ impl Document {
         self
     }
 
+    /// Add some text as a paragraph of its own.
+    ///
+    /// Elements pushed one after another are rendered on consecutive lines,
+    /// which LaTeX joins into a single paragraph. This makes sure there's a
+    /// blank line on either side of the text so it stays separate.
+    ///
+    /// ```rust
+    /// use latex::Document;
+    ///
+    /// let mut doc = Document::default();
+    /// doc.push_paragraph("First.").push_paragraph("Second.");
+    ///
+    /// assert_eq!(doc.body_string().unwrap(), "First.\n\nSecond.\n\n");
+    /// ```
+    pub fn push_paragraph(&mut self, text: &str) -> &mut Self {
+        self.push_blank_line();
+        self.push(text);
+        self.push_blank_line();
+        self
+    }
+
+    /// Add a paragraph break, unless the document is empty or already ends
+    /// with one.
+    fn push_blank_line(&mut self) {
+        if self
+            .elements
+            .last()
+            .is_some_and(|last| *last != Element::ParagraphBreak)
+        {
+            self.elements.push(Element::ParagraphBreak);
+        }
+    }
+
+    /// Add every element from an iterator to the `Document`, in order.
+    pub fn push_all<I>(&mut self, elements: I) -> &mut Self
+    where
+        I: IntoIterator<Item = Element>,
+    {
+        self.elements.extend(elements);
+        self
+    }
+
+    /// Turn this into a subfile of the main document at `main_path`, so it
+    /// can be compiled on its own as well as included with an
+    /// `Element::Subfile`.
+    ///
+    /// ```rust
+    /// use latex::{Document, DocumentClass};
+    ///
+    /// let mut chapter = Document::default();
+    /// chapter.as_subfile("../main.tex");
+    /// assert_eq!(chapter.class, DocumentClass::Subfile("../main.tex".to_string()));
+    /// ```
+    pub fn as_subfile(&mut self, main_path: &str) -> &mut Self {
+        self.class = DocumentClass::Subfile(main_path.to_string());
+        self
+    }
+
+    /// Set whether this is a draft or the final version of the document.
+    ///
+    /// The mode is passed to the document class as an option, and todo notes
+    /// are left out of the final version. Drafts also load `hyperref` with
+    /// its `draft` option, which turns off links.
+    pub fn set_mode(&mut self, mode: DocumentMode) -> &mut Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Get the document's mode, if one has been set.
+    pub fn mode(&self) -> Option<&DocumentMode> {
+        self.mode.as_ref()
+    }
+
+    /// Will todo notes be left out when this document is rendered? They are
+    /// only hidden in `DocumentMode::Final`.
+    pub fn todos_hidden(&self) -> bool {
+        self.mode == Some(DocumentMode::Final)
+    }
+
     /// Iterate over the Elements in this document.
-    pub fn iter(&self) -> Iter<Element> {
+    pub fn iter(&self) -> Iter<'_, Element> {
         self.elements.iter()
     }
 
+    /// Call a function on every element in the document, including elements
+    /// nested inside sections, proofs and other wrappers.
+    ///
+    /// Each element is passed to `f` before its children, so `f` may replace
+    /// a wrapper and have the new one's children walked instead.
+    ///
+    /// ```rust
+    /// use latex::{Document, Element};
+    ///
+    /// let mut doc = Document::default();
+    /// doc.push(Element::TikzPicture(r"\draw (0,0) -- (1,1);".to_string()));
+    ///
+    /// // swap pictures for placeholders in a draft
+    /// doc.visit_mut(|element| {
+    ///     if let Element::TikzPicture(_) = *element {
+    ///         *element = Element::from("[picture]");
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(doc.body_string().unwrap(), "[picture]\n");
+    /// ```
+    pub fn visit_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Element),
+    {
+        for element in &mut self.elements {
+            visit_element_mut(element, &mut f);
+        }
+    }
+
+    /// Append another document's elements to this one, e.g. when assembling
+    /// a report from documents for each chapter.
+    ///
+    /// This document's preamble is kept, but any packages `other` imports
+    /// which aren't already used are added to it. The rest of `other`'s
+    /// preamble is dropped.
+    pub fn merge(&mut self, other: Document) -> &mut Self {
+        for item in other.preamble.contents {
+            if let PreambleElement::UsePackage { package, argument } = item {
+                if !self.preamble.uses_package(&package) {
+                    self.preamble.insert_package(&package, argument.as_deref());
+                }
+            }
+        }
+
+        self.elements.extend(other.elements);
+        self
+    }
+
+    /// Apply a `Template` to the document, wrapping its elements in the
+    /// template's header and footer and adding the template's preamble.
+    ///
+    /// Anything the document already sets takes priority: packages which
+    /// are already imported aren't imported again (even with different
+    /// options), anything else already in the preamble isn't repeated, and
+    /// the template's title, author and date are only used if the document
+    /// doesn't have its own.
+    pub fn apply_template(&mut self, template: &Template) -> &mut Self {
+        for item in template.preamble.iter() {
+            match *item {
+                PreambleElement::UsePackage {
+                    ref package,
+                    ref argument,
+                } => {
+                    if !self.preamble.uses_package(package) {
+                        self.preamble.insert_package(package, argument.as_deref());
+                    }
+                }
+                _ if self.preamble.iter().any(|existing| existing == item) => {}
+                _ => {
+                    self.preamble.push(item.clone());
+                }
+            }
+        }
+
+        if self.preamble.title.is_none() {
+            self.preamble.title = template.preamble.title.clone();
+        }
+        if self.preamble.author.is_none() {
+            self.preamble.author = template.preamble.author.clone();
+        }
+        if self.preamble.date.is_none() {
+            self.preamble.date = template.preamble.date.clone();
+        }
+
+        let body = mem::replace(&mut self.elements, template.header.clone());
+        self.elements.extend(body);
+        self.elements.extend(template.footer.iter().cloned());
+        self
+    }
+
+    /// Render everything which comes before `\begin{document}`: the
+    /// `\documentclass` line and the preamble, including any packages the
+    /// document's elements require.
+    ///
+    /// This is useful when the document's shell comes from somewhere else,
+    /// such as a template. Joining `preamble_string()`, `\begin{document}`,
+    /// `body_string()` and `\end{document}` gives the same output as
+    /// `print()`.
+    pub fn preamble_string(&self) -> Result<String, visitor::RenderError> {
+        visitor::preamble_string(self)
+    }
+
+    /// Predict the number each label will resolve to when the document is
+    /// compiled, without running LaTeX. This is mostly useful for tests.
+    ///
+    /// Section, equation, figure and table counters are simulated in
+    /// document order using LaTeX's defaults, numbering them within chapters
+    /// for the book and report classes. Figures and tables are only found in
+    /// `figure` and `table` environments. Counters changed with
+    /// `Element::SetCounter` or in raw TeX aren't taken into account.
+    pub fn resolve_numbers(&self) -> HashMap<String, String> {
+        visitor::resolve_numbers(self)
+    }
+
+    /// Count the sections, figures, tables and equations in the document,
+    /// including any nested inside other elements.
+    pub fn element_summary(&self) -> visitor::ElementSummary {
+        visitor::element_summary(self)
+    }
+
+    /// Get every package the rendered document will import, in the order
+    /// they will appear. This includes both the packages explicitly added to
+    /// the preamble and any which are added automatically because an element
+    /// needs them, so it matches the output of `print()`.
+    pub fn required_packages(&self) -> Result<Vec<Package>, Error> {
+        visitor::required_packages(self)
+    }
+
+    /// Render just the document's body, without the `\begin{document}`
+    /// wrapper. See `preamble_string()`.
+    pub fn body_string(&self) -> Result<String, visitor::RenderError> {
+        visitor::body_string(self)
+    }
+
+    /// Get an indented outline of the document's structure, showing the
+    /// type of each element instead of its LaTeX. This is mainly useful for
+    /// debugging and in tests.
+    ///
+    /// ```rust
+    /// use latex::{Document, Section};
+    ///
+    /// let mut section = Section::new("Introduction");
+    /// section.push("Some text.");
+    /// let mut doc = Document::default();
+    /// doc.push(section);
+    ///
+    /// let should_be = "Document(article)\n  Section(\"Introduction\")\n    Paragraph\n";
+    /// assert_eq!(doc.debug_tree(), should_be);
+    /// ```
+    pub fn debug_tree(&self) -> String {
+        visitor::debug_tree(self)
+    }
+
     /// A convience method to include one document into
     /// another by cloning the individual nodes.
     pub fn push_doc(&mut self, doc: &Document) -> &mut Self {
@@ -55,6 +331,32 @@ impl Document {
     }
 }
 
+/// Call `f` on an element and then recursively on each of its children.
+fn visit_element_mut<F>(element: &mut Element, f: &mut F)
+where
+    F: FnMut(&mut Element),
+{
+    f(element);
+
+    let children = match *element {
+        Element::Section(ref mut section) => section.iter_mut(),
+        Element::Proof(ref mut proof) => proof.iter_mut(),
+        Element::Landscape(ref mut landscape) => landscape.iter_mut(),
+        Element::AdjustBox(ref mut adjust_box) => adjust_box.iter_mut(),
+        Element::WrapFigure(ref mut figure) => figure.iter_mut(),
+        Element::ColorBox(ref mut color_box) => color_box.iter_mut(),
+        Element::Aligned(ref mut aligned) => aligned.iter_mut(),
+        Element::Comment {
+            ref mut content, ..
+        } => content.iter_mut(),
+        _ => return,
+    };
+
+    for child in children {
+        visit_element_mut(child, f);
+    }
+}
+
 impl Deref for Document {
     type Target = Vec<Element>;
 
@@ -80,6 +382,22 @@ pub enum Element {
     /// instead add it to a `Section` so that if you are walking the AST later
     /// on things make sense.
     Para(Paragraph),
+    /// A `\part` heading, for book and report classes. A starred part is
+    /// left unnumbered.
+    Part {
+        /// The part's title.
+        title: String,
+        /// Use the unnumbered `\part*` form.
+        starred: bool,
+    },
+    /// A `\chapter` heading, for book and report classes. A starred chapter
+    /// is left unnumbered.
+    Chapter {
+        /// The chapter's title.
+        title: String,
+        /// Use the unnumbered `\chapter*` form.
+        starred: bool,
+    },
     /// A section.
     Section(Section),
     /// The table of contents.
@@ -88,8 +406,104 @@ pub enum Element {
     TitlePage,
     /// Clear the page.
     ClearPage,
+    /// A blank line, which ends the current paragraph.
+    ParagraphBreak,
+    /// Start a new page with different margins (e.g. `left=1cm,right=1cm`),
+    /// using `\newgeometry` from the `geometry` package.
+    NewGeometry(String),
+    /// Go back to the document's usual margins after an
+    /// `Element::NewGeometry`, starting a new page.
+    RestoreGeometry,
+    /// Change the style of the current page only with `\thispagestyle`
+    /// (e.g. to hide the page number on a title page).
+    ///
+    /// The headers for a `PageStyle::Fancy` style still need to be set up
+    /// using `Preamble::page_style()`.
+    ThisPageStyle(PageStyle),
+    /// Switch the style of page numbers with `\pagenumbering`, which also
+    /// restarts the numbering from 1 (e.g. roman numerals for the front
+    /// matter, then arabic for the main text).
+    PageNumbering(NumberStyle),
+    /// Set a counter (e.g. `section` or `footnote`) to a value with
+    /// `\setcounter`.
+    SetCounter {
+        /// The counter's name, without a backslash.
+        name: String,
+        /// The counter's new value.
+        value: i64,
+    },
+    /// Add to (or, with a negative `delta`, subtract from) a counter with
+    /// `\addtocounter`.
+    AddToCounter {
+        /// The counter's name, without a backslash.
+        name: String,
+        /// The amount to add.
+        delta: i64,
+    },
+    /// Placeholder text from the `lipsum` package: either its default
+    /// paragraphs, or the numbered paragraphs in a range (e.g. `1..=3`).
+    /// Rendering a reversed range (e.g. `3..=1`) is an error.
+    Lipsum(Option<RangeInclusive<usize>>),
+    /// Print the endnotes collected so far (`\theendnotes`).
+    PrintEndnotes,
+    /// Print biblatex's list of shorthands (`\printshorthands`).
+    PrintShorthands,
+    /// Print the nomenclature collected from the document's
+    /// `ParagraphElement::Nomenclature` entries (`\printnomenclature`).
+    PrintNomenclature,
+    /// Pages from an external PDF, using `\includepdf` from the `pdfpages`
+    /// package.
+    IncludePdf {
+        /// The PDF to include.
+        path: PathBuf,
+        /// The pages to include (e.g. `1..=3`), or every page if `None`.
+        /// Rendering a reversed range (e.g. `3..=1`) is an error.
+        pages: Option<RangeInclusive<usize>>,
+    },
     /// An `align` environment for containing a bunch of equations.
     Align(Align),
+    /// A single equation broken over several lines.
+    LongEquation(LongEquation),
+
+    /// A `tikzpicture` environment, with its body copied across verbatim.
+    TikzPicture(String),
+
+    /// A plot of some data points.
+    Plot(Plot),
+    /// A table of data read from a CSV file.
+    CsvTable(CsvTable),
+
+    /// A proof, using the `amsthm` package.
+    Proof(Proof),
+
+    /// Elements which are set on landscape pages.
+    Landscape(Landscape),
+
+    /// Elements which are shrunk to fit a maximum width.
+    AdjustBox(AdjustBox),
+    /// A figure which text flows around.
+    WrapFigure(WrapFigure),
+    /// A coloured callout box.
+    ColorBox(ColorBox),
+    /// Elements which are centred or flush against a margin.
+    Aligned(Aligned),
+
+    /// A hand-written `thebibliography` environment.
+    ManualBibliography(ManualBibliography),
+    /// The definitions of the acronyms used in the document.
+    Acronyms(AcronymList),
+
+    /// A block which can be switched on or off from the preamble (e.g. the
+    /// solutions in a problem sheet), using the `comment` package.
+    Comment {
+        /// The environment's name, which is declared with `\includecomment`
+        /// or `\excludecomment`.
+        name: String,
+        /// Whether the block's contents are shown.
+        included: bool,
+        /// The elements inside the block.
+        content: Vec<Element>,
+    },
 
     /// A generic environment and its lines.
     Environment(String, Vec<String>),
@@ -105,6 +519,20 @@ pub enum Element {
     List(List),
     /// A generic include statement
     Input(String),
+    /// Include a document written with `DocumentClass::Subfile`, using
+    /// `\subfile` from the `subfiles` package.
+    Subfile(String),
+    /// Include a file with `\import` from the `import` package, which lets
+    /// the file use paths relative to its own directory.
+    Import {
+        /// The directory the file is in (e.g. `chapters/`).
+        dir: String,
+        /// The file's name, relative to `dir`.
+        file: String,
+    },
+    /// The contents of an external file, which is read when the document is
+    /// rendered and copied into the output as-is.
+    InlineFile(PathBuf),
 
     // Add a dummy element so we can expand later on without breaking stuff
     #[doc(hidden)]
@@ -124,6 +552,15 @@ impl<'a> From<&'a str> for Element {
     }
 }
 
+impl From<ParagraphElement> for Element {
+    /// Wrap a single `ParagraphElement` in its own paragraph.
+    fn from(other: ParagraphElement) -> Self {
+        let mut para = Paragraph::new();
+        para.push(other);
+        Element::Para(para)
+    }
+}
+
 impl From<List> for Element {
     fn from(other: List) -> Self {
         Element::List(other)
@@ -136,6 +573,81 @@ impl From<Align> for Element {
     }
 }
 
+impl From<Equation> for Element {
+    /// Wrap a single `Equation` in its own `align` environment.
+    fn from(other: Equation) -> Self {
+        let mut align = Align::new();
+        align.push(other);
+        Element::Align(align)
+    }
+}
+
+impl From<LongEquation> for Element {
+    fn from(other: LongEquation) -> Self {
+        Element::LongEquation(other)
+    }
+}
+
+impl From<Plot> for Element {
+    fn from(other: Plot) -> Self {
+        Element::Plot(other)
+    }
+}
+
+impl From<CsvTable> for Element {
+    fn from(other: CsvTable) -> Self {
+        Element::CsvTable(other)
+    }
+}
+
+impl From<AdjustBox> for Element {
+    fn from(other: AdjustBox) -> Self {
+        Element::AdjustBox(other)
+    }
+}
+
+impl From<WrapFigure> for Element {
+    fn from(other: WrapFigure) -> Self {
+        Element::WrapFigure(other)
+    }
+}
+
+impl From<Aligned> for Element {
+    fn from(other: Aligned) -> Self {
+        Element::Aligned(other)
+    }
+}
+
+impl From<ColorBox> for Element {
+    fn from(other: ColorBox) -> Self {
+        Element::ColorBox(other)
+    }
+}
+
+impl From<Landscape> for Element {
+    fn from(other: Landscape) -> Self {
+        Element::Landscape(other)
+    }
+}
+
+impl From<AcronymList> for Element {
+    fn from(other: AcronymList) -> Self {
+        Element::Acronyms(other)
+    }
+}
+
+impl From<ManualBibliography> for Element {
+    fn from(other: ManualBibliography) -> Self {
+        Element::ManualBibliography(other)
+    }
+}
+
+impl From<Proof> for Element {
+    fn from(other: Proof) -> Self {
+        Element::Proof(other)
+    }
+}
+
 impl From<Section> for Element {
     fn from(other: Section) -> Self {
         Element::Section(other)
@@ -160,24 +672,23 @@ where
 }
 
 /// The kind of Document being generated.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 #[allow(missing_docs)]
 pub enum DocumentClass {
+    #[default]
     Article,
     Book,
     Report,
     /// A partial document comes without header and footer.
     /// It is intended to be included (`include{}`) in some other tex file.
     Part,
+    /// A document for the `subfiles` package, which can be compiled on its
+    /// own or pulled into the main document (at the provided path) with an
+    /// `Element::Subfile`. It uses the main document's preamble.
+    Subfile(String),
     Other(String),
 }
 
-impl Default for DocumentClass {
-    fn default() -> Self {
-        DocumentClass::Article
-    }
-}
-
 impl Display for DocumentClass {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match *self {
@@ -185,11 +696,51 @@ impl Display for DocumentClass {
             DocumentClass::Book => write!(f, "book"),
             DocumentClass::Report => write!(f, "report"),
             DocumentClass::Part => write!(f, ""),
+            DocumentClass::Subfile(_) => write!(f, "subfiles"),
             DocumentClass::Other(ref s) => write!(f, "{}", *s),
         }
     }
 }
 
+/// Whether a document is a draft or the final version.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DocumentMode {
+    /// A draft, where LaTeX marks overfull boxes and todo notes are shown.
+    Draft,
+    /// The final version, without any todo notes.
+    Final,
+}
+
+impl Display for DocumentMode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            DocumentMode::Draft => write!(f, "draft"),
+            DocumentMode::Final => write!(f, "final"),
+        }
+    }
+}
+
+/// The TeX engine used to compile a document.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Engine {
+    /// The classic `pdflatex`, which doesn't support system fonts.
+    Pdflatex,
+    /// `xelatex`, which reads Unicode and can use system fonts.
+    Xelatex,
+    /// `lualatex`, which reads Unicode and can use system fonts.
+    Lualatex,
+}
+
+impl Display for Engine {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Engine::Pdflatex => write!(f, "pdflatex"),
+            Engine::Xelatex => write!(f, "xelatex"),
+            Engine::Lualatex => write!(f, "lualatex"),
+        }
+    }
+}
+
 impl Extend<Element> for Document {
     fn extend<T: IntoIterator<Item=Element>>(&mut self, iter:T) {
     for elem in iter {
@@ -198,6 +749,15 @@ impl Extend<Element> for Document {
   }
 }
 
+impl FromIterator<Element> for Document {
+    /// Collect some elements into a new `DocumentClass::Article` document.
+    fn from_iter<T: IntoIterator<Item = Element>>(iter: T) -> Self {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.extend(iter);
+        doc
+    }
+}
+
 /// An element of the document's preamble.
 #[derive(Clone, Debug, PartialEq)]
 #[allow(missing_docs)]
@@ -214,10 +774,55 @@ pub enum PreambleElement {
         default_arg: Option<String>,
         definition: String
     },
+    /// Create a `\renewcommand` line in latex, redefining an existing command
+    RenewCommand {
+        name: String,
+        args_num: Option<usize>,
+        default_arg: Option<String>,
+        definition: String,
+    },
+    /// Create a `\newenvironment` line in latex
+    NewEnvironment {
+        name: String,
+        args_num: Option<usize>,
+        begin: String,
+        end: String,
+    },
+    /// Set a length register with `\setlength`.
+    SetLength { register: String, value: String },
+    /// Declare an operator such as `\argmax` with `\DeclareMathOperator`.
+    /// Starred operators take their limits above and below, like `\lim`.
+    DeclareMathOperator {
+        name: String,
+        rendered: String,
+        starred: bool,
+    },
+    /// Define a command with `xparse`'s `\NewDocumentCommand`, which allows
+    /// optional arguments with defaults (e.g. `O{default}m`).
+    NewDocumentCommand {
+        name: String,
+        arg_spec: String,
+        body: String,
+    },
+    /// Set the style used for page headers and footers.
+    PageStyle(PageStyle),
+    /// Configure `hyperref` with `\hypersetup`. This is always rendered
+    /// after the last package import, so `hyperref` has been loaded by then.
+    HyperSetup(HyperSetup),
     /// An escape hatch for including an arbitrary bit of TeX in a preamble.
     UserDefined(String),
 }
 
+/// A package imported by a document, as reported by
+/// `Document::required_packages()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Package {
+    /// The package's name.
+    pub name: String,
+    /// Any options passed to the package.
+    pub options: Option<String>,
+}
+
 /// A node representing the document's preamble.
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Preamble {
@@ -225,6 +830,8 @@ pub struct Preamble {
     pub author: Option<String>,
     /// An optional title for the document.
     pub title: Option<String>,
+    /// The date shown by `\maketitle`.
+    pub date: Option<String>,
     contents: Vec<PreambleElement>,
 }
 
@@ -235,12 +842,41 @@ impl Preamble {
         self
     }
 
+    /// Add an author with a `\thanks{}` note (e.g. their affiliation).
+    ///
+    /// Calling this several times, or after `author()`, adds each author in
+    /// turn, separated by `\and`.
+    pub fn author_with_thanks(&mut self, name: &str, thanks: &str) -> &mut Self {
+        let entry = format!(r"{}\thanks{{{}}}", name, thanks);
+        self.author = Some(match self.author.take() {
+            Some(existing) => format!(r"{} \and {}", existing, entry),
+            None => entry,
+        });
+        self
+    }
+
     /// Set the document title.
     pub fn title(&mut self, name: &str) -> &mut Self {
         self.title = Some(name.to_string());
         self
     }
 
+    /// Set the date shown by `\maketitle` (e.g. `\today`).
+    pub fn date(&mut self, date: &str) -> &mut Self {
+        self.date = Some(date.to_string());
+        self
+    }
+
+    /// Set the date to a specific day, formatted by `datetime2` as an ISO
+    /// date (e.g. `2020-03-14`).
+    pub fn date_formatted(&mut self, year: u32, month: u32, day: u32) -> &mut Self {
+        self.require_package("datetime2");
+        self.date(&format!(
+            r"\DTMdisplaydate{{{}}}{{{}}}{{{}}}{{-1}}",
+            year, month, day
+        ))
+    }
+
     /// Add a package import to the preamble.
     pub fn use_package(&mut self, name: &str) -> &mut Self {
         self.contents.push(PreambleElement::UsePackage {
@@ -250,6 +886,230 @@ impl Preamble {
         self
     }
 
+    /// Add a package import to the preamble, unless the package is already
+    /// being used.
+    fn require_package(&mut self, name: &str) -> &mut Self {
+        if !self.uses_package(name) {
+            self.use_package(name);
+        }
+        self
+    }
+
+    /// Add a package import with an argument to the preamble, unless the
+    /// package is already being used.
+    fn require_package_with(&mut self, name: &str, argument: &str) -> &mut Self {
+        if !self.uses_package(name) {
+            self.contents.push(PreambleElement::UsePackage {
+                package: name.to_string(),
+                argument: Some(argument.to_string()),
+            });
+        }
+        self
+    }
+
+    /// Import a package alongside the other package imports, after the last
+    /// `\usepackage` already in the preamble.
+    pub(crate) fn insert_package(&mut self, name: &str, argument: Option<&str>) {
+        let position = self
+            .contents
+            .iter()
+            .rposition(|elem| matches!(*elem, PreambleElement::UsePackage { .. }))
+            .map_or(0, |i| i + 1);
+
+        self.contents.insert(
+            position,
+            PreambleElement::UsePackage {
+                package: name.to_string(),
+                argument: argument.map(ToString::to_string),
+            },
+        );
+    }
+
+    /// Add an option to each import of a package, unless it's already one of
+    /// the package's options.
+    pub(crate) fn add_package_option(&mut self, name: &str, option: &str) {
+        for elem in &mut self.contents {
+            if let PreambleElement::UsePackage {
+                ref package,
+                ref mut argument,
+            } = *elem
+            {
+                let has_option = argument
+                    .as_ref()
+                    .is_some_and(|a| a.split(',').any(|o| o.trim() == option));
+                if package != name || has_option {
+                    continue;
+                }
+
+                *argument = Some(match argument.take() {
+                    Some(existing) => format!("{},{}", existing, option),
+                    None => option.to_string(),
+                });
+            }
+        }
+    }
+
+    /// Does the preamble already import the specified package?
+    pub fn uses_package(&self, name: &str) -> bool {
+        self.contents.iter().any(|elem| match *elem {
+            PreambleElement::UsePackage { ref package, .. } => package == name,
+            _ => false,
+        })
+    }
+
+    /// Turn the `microtype` package on or off. Enabling it imports the
+    /// package (once); disabling it removes any existing import.
+    pub fn microtype(&mut self, enabled: bool) -> &mut Self {
+        if enabled {
+            self.require_package("microtype");
+        } else {
+            self.contents.retain(|elem| match *elem {
+                PreambleElement::UsePackage { ref package, .. } => package != "microtype",
+                _ => true,
+            });
+        }
+        self
+    }
+
+    /// Set up some sensible typography defaults: `T1` font encoding (so
+    /// accented characters hyphenate and copy properly) and `microtype`.
+    pub fn typography_defaults(&mut self) -> &mut Self {
+        self.require_package_with("fontenc", "T1");
+        self.microtype(true)
+    }
+
+    /// Set up the input and font encodings in the way the engine expects.
+    ///
+    /// For `pdflatex` this loads `inputenc` (for UTF-8 source) and `fontenc`
+    /// with `T1`. The Unicode engines already read UTF-8 and `inputenc`
+    /// doesn't work with them, so they get `fontspec` instead.
+    pub fn encoding_for_engine(&mut self, engine: Engine) -> &mut Self {
+        match engine {
+            Engine::Pdflatex => {
+                self.require_package_with("inputenc", "utf8");
+                self.require_package_with("fontenc", "T1");
+            }
+            Engine::Xelatex | Engine::Lualatex => {
+                self.require_package("fontspec");
+            }
+        }
+        self
+    }
+
+    /// Set the document's main font with `fontspec`, importing the package.
+    ///
+    /// This only works with `xelatex` and `lualatex`, so `validate()` will
+    /// complain if the document's `engine` is `Engine::Pdflatex`.
+    pub fn main_font(&mut self, name: &str) -> &mut Self {
+        self.require_package("fontspec");
+        self.contents.push(PreambleElement::UserDefined(format!(
+            r"\setmainfont{{{}}}",
+            name
+        )));
+        self
+    }
+
+    /// Set the page style used for headers and footers.
+    ///
+    /// A `PageStyle::Fancy` style will also import the `fancyhdr` package.
+    pub fn page_style(&mut self, style: PageStyle) -> &mut Self {
+        if let PageStyle::Fancy(_) = style {
+            self.require_package("fancyhdr");
+        }
+
+        self.contents.push(PreambleElement::PageStyle(style));
+        self
+    }
+
+    /// Change the table of contents' title, importing the `tocloft` package.
+    pub fn toc_title(&mut self, title: &str) -> &mut Self {
+        self.require_package("tocloft");
+        self.contents.push(PreambleElement::UserDefined(format!(
+            r"\renewcommand{{\contentsname}}{{{}}}",
+            title
+        )));
+        self
+    }
+
+    /// Adjust the table of contents' dot leaders, importing the `tocloft`
+    /// package.
+    pub fn toc_style(&mut self, style: TocStyle) -> &mut Self {
+        self.require_package("tocloft");
+        if style.section_dots {
+            self.contents.push(PreambleElement::UserDefined(
+                r"\renewcommand{\cftsecleader}{\cftdotfill{\cftdotsep}}".to_string(),
+            ));
+        }
+        if let Some(separation) = style.dot_separation {
+            self.contents.push(PreambleElement::UserDefined(format!(
+                r"\renewcommand{{\cftdotsep}}{{{}}}",
+                separation
+            )));
+        }
+        self
+    }
+
+    /// Set the line spacing for the document, importing the `setspace`
+    /// package.
+    pub fn line_spacing(&mut self, spacing: LineSpacing) -> &mut Self {
+        self.require_package("setspace");
+        self.contents
+            .push(PreambleElement::UserDefined(spacing.to_string()));
+        self
+    }
+
+    /// Set the directories `\includegraphics` searches for images with
+    /// `\graphicspath`, importing the `graphicx` package. A trailing `/` is
+    /// added to each directory if it's missing, since LaTeX needs one.
+    ///
+    /// ```rust
+    /// # use latex::Preamble;
+    /// let mut preamble = Preamble::default();
+    /// preamble.graphics_path(&["figures", "images/"]);
+    /// // \graphicspath{{figures/}{images/}}
+    /// ```
+    pub fn graphics_path(&mut self, directories: &[&str]) -> &mut Self {
+        let directories: String = directories
+            .iter()
+            .map(|dir| {
+                if dir.ends_with('/') {
+                    format!("{{{}}}", dir)
+                } else {
+                    format!("{{{}/}}", dir)
+                }
+            })
+            .collect();
+
+        self.require_package("graphicx");
+        self.contents.push(PreambleElement::UserDefined(format!(
+            r"\graphicspath{{{}}}",
+            directories
+        )));
+        self
+    }
+
+    /// Configure how captions are formatted with `\captionsetup`, importing
+    /// the `caption` package.
+    ///
+    /// ```rust
+    /// # use latex::Preamble;
+    /// let mut preamble = Preamble::default();
+    /// preamble.caption_setup(&[("font", "small"), ("labelfont", "bf")]);
+    /// ```
+    pub fn caption_setup(&mut self, options: &[(&str, &str)]) -> &mut Self {
+        let options: Vec<_> = options
+            .iter()
+            .map(|&(key, value)| format!("{}={}", key, value))
+            .collect();
+
+        self.require_package("caption");
+        self.contents.push(PreambleElement::UserDefined(format!(
+            r"\captionsetup{{{}}}",
+            options.join(",")
+        )));
+        self
+    }
+
     /// Interface of most commonly used way to write a `/newcommand` line in latex.  
     /// If you want to create `/newcommand` in 
     /// other ways(like add default argument or do not assign the num of arguments), 
@@ -271,8 +1131,238 @@ impl Preamble {
         self
     }
 
+    /// Like `new_command()`, but for a text macro which ends with `\xspace`
+    /// so that a space is kept after it when one is needed (e.g. `\etal`
+    /// followed by a word). This also imports the `xspace` package.
+    pub fn new_command_with_xspace(
+        &mut self,
+        name: &str,
+        args_num: usize,
+        definition: &str,
+    ) -> &mut Self {
+        self.require_package("xspace");
+        self.new_command(name, args_num, &format!(r"{}\xspace", definition))
+    }
+
+    /// Redefine an existing command with `\renewcommand`, using the same
+    /// arguments as `new_command()`.
+    pub fn renew_command(&mut self, name: &str, args_num: usize, definition: &str) -> &mut Self {
+        self.contents.push(PreambleElement::RenewCommand {
+            name: String::from(name),
+            args_num: Some(args_num),
+            default_arg: None,
+            definition: String::from(definition),
+        });
+        self
+    }
+
+    /// Define a new environment with `\newenvironment`, where `begin` and
+    /// `end` are the code run at the start and end of the environment.
+    ///
+    /// ```rust
+    /// # use latex::Preamble;
+    /// let mut preamble = Preamble::default();
+    /// preamble.new_environment("note", r"\begin{quote}\textbf{#1:}", r"\end{quote}", Some(1));
+    /// ```
+    pub fn new_environment(
+        &mut self,
+        name: &str,
+        begin: &str,
+        end: &str,
+        args_num: Option<usize>,
+    ) -> &mut Self {
+        self.contents.push(PreambleElement::NewEnvironment {
+            name: name.to_string(),
+            args_num,
+            begin: begin.to_string(),
+            end: end.to_string(),
+        });
+        self
+    }
+
+    /// Declare a new math operator (e.g. `argmax`) with
+    /// `\DeclareMathOperator`, importing the `amsmath` package which provides
+    /// it.
+    ///
+    /// The starred form places subscripts underneath the operator in display
+    /// math, the same as `\lim`.
+    ///
+    /// ```rust
+    /// # use latex::Preamble;
+    /// let mut preamble = Preamble::default();
+    /// preamble.declare_math_operator("argmax", r"arg\,max", true);
+    /// ```
+    pub fn declare_math_operator(
+        &mut self,
+        name: &str,
+        rendered: &str,
+        starred: bool,
+    ) -> &mut Self {
+        self.require_package("amsmath");
+        self.contents.push(PreambleElement::DeclareMathOperator {
+            name: name.trim_start_matches('\\').to_string(),
+            rendered: rendered.to_string(),
+            starred,
+        });
+        self
+    }
+
+    /// Define a command with `\NewDocumentCommand`, importing the `xparse`
+    /// package which provides it.
+    ///
+    /// The `arg_spec` describes each argument, e.g. `O{default}m` for an
+    /// optional argument defaulting to `default` and a mandatory one.
+    ///
+    /// ```rust
+    /// # use latex::Preamble;
+    /// let mut preamble = Preamble::default();
+    /// preamble.new_document_command("greet", "O{Hello}m", r"#1, #2!");
+    /// ```
+    pub fn new_document_command(&mut self, name: &str, arg_spec: &str, body: &str) -> &mut Self {
+        self.require_package("xparse");
+        self.contents.push(PreambleElement::NewDocumentCommand {
+            name: name.trim_start_matches('\\').to_string(),
+            arg_spec: arg_spec.to_string(),
+            body: body.to_string(),
+        });
+        self
+    }
+
+    /// Set a length register (e.g. `parindent`) to the provided value,
+    /// rendering `\setlength{\register}{value}`.
+    ///
+    /// The register may be given with or without its leading backslash, but
+    /// must otherwise be a valid TeX control sequence name (letters and `@`).
+    pub fn set_length(&mut self, register: &str, value: &str) -> Result<&mut Self, Error> {
+        let name = register.trim_start_matches('\\');
+
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphabetic() || c == '@') {
+            bail!("\"{}\" is not a valid length register", register);
+        }
+
+        self.contents.push(PreambleElement::SetLength {
+            register: name.to_string(),
+            value: value.to_string(),
+        });
+        Ok(self)
+    }
+
+    /// Define a colour with `xcolor`'s `\definecolor`, importing the package.
+    ///
+    /// The colour model must be one of `rgb`, `RGB`, `HTML` or `cmyk`.
+    ///
+    /// ```rust
+    /// # use latex::Preamble;
+    /// let mut preamble = Preamble::default();
+    /// preamble.define_color("brand", "RGB", "0,51,102").unwrap();
+    /// assert!(preamble.define_color("brand", "hsl", "210,100,20").is_err());
+    /// ```
+    pub fn define_color(
+        &mut self,
+        name: &str,
+        model: &str,
+        spec: &str,
+    ) -> Result<&mut Self, Error> {
+        if !["rgb", "RGB", "HTML", "cmyk"].contains(&model) {
+            bail!("\"{}\" is not a supported colour model", model);
+        }
+
+        self.require_package("xcolor");
+        self.contents.push(PreambleElement::UserDefined(format!(
+            r"\definecolor{{{}}}{{{}}}{{{}}}",
+            name, model, spec
+        )));
+        Ok(self)
+    }
+
+    /// Only compile the listed files when they are `\include`d, rendering
+    /// `\includeonly{a,b}`. A `.tex` extension is stripped.
+    pub fn include_only(&mut self, files: &[&str]) -> &mut Self {
+        let files: Vec<_> = files
+            .iter()
+            .map(|f| f.strip_suffix(".tex").unwrap_or(f))
+            .collect();
+
+        self.contents.push(PreambleElement::UserDefined(format!(
+            r"\includeonly{{{}}}",
+            files.join(",")
+        )));
+        self
+    }
+
+    /// Set up `biblatex` with the provided options, and register `resource`
+    /// as the `.bib` file to read references from.
+    ///
+    /// ```rust
+    /// # use latex::{BiblatexOptions, Preamble};
+    /// let mut preamble = Preamble::default();
+    /// preamble.biblatex(BiblatexOptions::default(), "main.bib");
+    /// ```
+    pub fn biblatex(&mut self, options: BiblatexOptions, resource: &str) -> &mut Self {
+        let options = options.to_string();
+
+        self.contents.push(PreambleElement::UsePackage {
+            package: "biblatex".to_string(),
+            argument: if options.is_empty() {
+                None
+            } else {
+                Some(options)
+            },
+        });
+        self.contents.push(PreambleElement::UserDefined(format!(
+            r"\addbibresource{{{}}}",
+            resource
+        )));
+        self
+    }
+
+    /// Configure `hyperref` with `\hypersetup`, importing the package.
+    ///
+    /// ```rust
+    /// # use latex::{HyperSetup, Preamble};
+    /// let mut preamble = Preamble::default();
+    /// preamble.hypersetup(HyperSetup {
+    ///     bookmarks_depth: Some(2),
+    ///     ..Default::default()
+    /// });
+    /// // \hypersetup{bookmarksdepth=2}
+    /// ```
+    pub fn hypersetup(&mut self, options: HyperSetup) -> &mut Self {
+        self.require_package("hyperref");
+        self.contents.push(PreambleElement::HyperSetup(options));
+        self
+    }
+
+    /// Load `minted` for highlighting code (unless it's already loaded), and
+    /// set the options every listing should use with `\setminted`.
+    pub fn minted_setup(&mut self, options: MintedOptions) -> &mut Self {
+        self.require_package("minted");
+        if let Some(ref dir) = options.cachedir {
+            self.add_package_option("minted", &format!("cachedir={}", dir));
+        }
+
+        let options = options.to_string();
+        if !options.is_empty() {
+            self.contents.push(PreambleElement::UserDefined(format!(
+                r"\setminted{{{}}}",
+                options
+            )));
+        }
+        self
+    }
+
+    /// Load `natbib` with the provided options (e.g. `round`, `authoryear`).
+    /// If `natbib` is already loaded, any new options are added to it.
+    pub fn natbib(&mut self, options: &[&str]) -> &mut Self {
+        self.require_package("natbib");
+        for option in options {
+            self.add_package_option("natbib", option);
+        }
+        self
+    }
+
     /// Iterate over each package used in the Preamble.
-    pub fn iter(&self) -> Iter<PreambleElement> {
+    pub fn iter(&self) -> Iter<'_, PreambleElement> {
         self.contents.iter()
     }
 
@@ -303,3 +1393,486 @@ impl Extend<PreambleElement> for Preamble {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layout::FancyHeaders;
+    use lists::ListKind;
+    use visitor::print;
+
+    #[test]
+    fn push_several_elements_at_once() {
+        let mut doc = Document::new(DocumentClass::Article);
+        let elements = vec![
+            Element::TitlePage,
+            Element::ClearPage,
+            Element::TableOfContents,
+        ];
+
+        doc.push_all(elements).push("Hello World");
+
+        assert_eq!(doc.len(), 4);
+        assert_eq!(doc[2], Element::TableOfContents);
+    }
+
+    #[test]
+    fn collect_elements_into_a_document() {
+        let elements = vec![
+            Element::TitlePage,
+            Element::from("Hello World"),
+            Element::ClearPage,
+        ];
+
+        let doc: Document = elements.clone().into_iter().collect();
+
+        assert_eq!(doc.class, DocumentClass::Article);
+        assert_eq!(*doc, elements);
+    }
+
+    #[test]
+    fn cloned_documents_are_independent() {
+        let mut original = Document::new(DocumentClass::Article);
+        original.preamble.title("Base Report");
+        let mut section = Section::new("Results");
+        section.push("Everything worked.");
+        original.push(section);
+
+        let mut variant = original.clone();
+        assert_eq!(variant, original);
+
+        variant
+            .preamble
+            .title("Variant Report")
+            .use_package("graphicx");
+        variant.push(Element::ClearPage);
+
+        assert_eq!(original.preamble.title, Some("Base Report".to_string()));
+        assert!(original.preamble.is_empty());
+        assert_eq!(original.len(), 1);
+        assert_ne!(variant, original);
+    }
+
+    #[test]
+    fn debug_tree_of_a_small_document() {
+        let mut background = Section::new("Background");
+        background.push(List::new(ListKind::Itemize));
+        let mut intro = Section::new("Intro");
+        intro.push("Some text.").push_section(background);
+
+        let mut doc = Document::new(DocumentClass::Report);
+        doc.push(Element::TableOfContents).push(intro);
+
+        let should_be = r#"Document(report)
+  TableOfContents
+  Section("Intro")
+    Paragraph
+    Section("Background")
+      List(Itemize)
+"#;
+        assert_eq!(doc.debug_tree(), should_be);
+    }
+
+    #[test]
+    fn merge_two_documents() {
+        let mut first = Document::new(DocumentClass::Report);
+        first
+            .preamble
+            .use_package("amsmath")
+            .use_package("graphicx")
+            .new_command("R", 0, r"\mathbb{R}");
+        first.push("Chapter one.");
+
+        let mut second = Document::default();
+        second
+            .preamble
+            .use_package("graphicx")
+            .use_package("booktabs");
+        second.push("Chapter two.").push(Element::ClearPage);
+
+        first.merge(second);
+
+        assert_eq!(first.class, DocumentClass::Report);
+        assert_eq!(first.len(), 3);
+        let packages: Vec<_> = first
+            .preamble
+            .iter()
+            .filter_map(|item| match *item {
+                PreambleElement::UsePackage { ref package, .. } => Some(package.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(packages, vec!["amsmath", "graphicx", "booktabs"]);
+
+        // the new package goes in with the others, before the command
+        let last = first.preamble.iter().last().unwrap();
+        assert!(!matches!(*last, PreambleElement::UsePackage { .. }));
+    }
+
+    #[test]
+    fn packages_for_a_figure_and_maths() {
+        let mut doc = Document::default();
+        doc.preamble.use_package("hyperref").use_package("geometry");
+        doc.push(Element::UserDefined(
+            r"\includegraphics{plot.png}".to_string(),
+        ))
+        .push(Element::Align(Align::from("E &= mc^2")));
+
+        let names: Vec<_> = doc
+            .required_packages()
+            .unwrap()
+            .into_iter()
+            .map(|package| package.name)
+            .collect();
+
+        assert_eq!(names, vec!["geometry", "graphicx", "amsmath", "hyperref"]);
+    }
+
+    #[test]
+    fn visit_every_nested_section() {
+        let mut inner = Section::new("Inner");
+        inner.push("Text.");
+        let mut outer = Section::new("Outer");
+        outer.push(inner);
+        let mut doc = Document::default();
+        doc.push(outer).push("Between.").push(Section::new("Last"));
+
+        doc.visit_mut(|element| {
+            if let Element::Section(ref mut section) = *element {
+                let label = format!("sec:{}", section.name.to_lowercase());
+                section.label(&label);
+            }
+        });
+
+        let mut labels: Vec<_> = doc.resolve_numbers().into_keys().collect();
+        labels.sort();
+
+        assert_eq!(labels, vec!["sec:inner", "sec:last", "sec:outer"]);
+    }
+
+    #[test]
+    fn pushed_paragraphs_are_separated() {
+        let mut doc = Document::default();
+        doc.push("Run together")
+            .push("with this.")
+            .push_paragraph("On its own.")
+            .push_paragraph("Also on its own.")
+            .push("After.");
+
+        let should_be = "Run together\nwith this.\n\nOn its own.\n\nAlso on its own.\n\nAfter.\n";
+        assert_eq!(doc.body_string().unwrap(), should_be);
+        assert_eq!(doc[2], Element::ParagraphBreak);
+        assert_eq!(doc.len(), 8);
+    }
+
+    #[test]
+    fn sections_bring_their_own_packages() {
+        let mut inner = Section::new("Diagram");
+        inner
+            .require_package("tikz", None)
+            .require_package("xcolor", Some("dvipsnames"));
+        let mut outer = Section::new("Design");
+        outer.push(inner);
+
+        let mut doc = Document::default();
+        doc.preamble.use_package("xcolor");
+        doc.push(outer);
+
+        let packages = doc.required_packages().unwrap();
+        let should_be = vec![
+            Package {
+                name: "xcolor".to_string(),
+                options: Some("dvipsnames".to_string()),
+            },
+            Package {
+                name: "tikz".to_string(),
+                options: None,
+            },
+        ];
+        assert_eq!(packages, should_be);
+
+        // and they're loaded when the document is rendered
+        let rendered = print(&doc).unwrap();
+        assert!(rendered.contains("\\usepackage{tikz}"));
+        assert!(rendered.contains("\\usepackage[dvipsnames]{xcolor}"));
+
+        // a highlight also needs xcolor, but without any options
+        let mut doc = Document::default();
+        doc.push(ParagraphElement::Highlight("Look".to_string()))
+            .push(doc_section_with_xcolor());
+        let xcolor = doc
+            .required_packages()
+            .unwrap()
+            .into_iter()
+            .find(|package| package.name == "xcolor")
+            .unwrap();
+        assert_eq!(xcolor.options, Some("dvipsnames".to_string()));
+    }
+
+    fn doc_section_with_xcolor() -> Section {
+        let mut section = Section::new("Colours");
+        section.require_package("xcolor", Some("dvipsnames"));
+        section
+    }
+
+    #[test]
+    fn url_package_mode_avoids_hyperref() {
+        let mut doc = Document::default();
+        doc.push(Element::UserDefined(
+            r"See \url{https://example.com}".to_string(),
+        ));
+
+        let names = |doc: &Document| -> Vec<String> {
+            doc.required_packages()
+                .unwrap()
+                .into_iter()
+                .map(|package| package.name)
+                .collect()
+        };
+        assert_eq!(names(&doc), vec!["hyperref"]);
+
+        doc.url_package = true;
+        assert_eq!(names(&doc), vec!["url"]);
+        assert_eq!(::validate(&doc).unwrap(), Vec::new());
+
+        doc.push(Element::UserDefined(r"and \ref{sec:a}".to_string()));
+        assert_eq!(names(&doc), vec!["url"]);
+        assert_eq!(
+            ::validate(&doc).unwrap(),
+            vec![::Diagnostic::NeedsHyperref(r"\ref{")]
+        );
+
+        doc.preamble.use_package("hyperref");
+        assert_eq!(::validate(&doc).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn push_content_without_wrapping() {
+        let mut list = List::new(ListKind::Itemize);
+        list.push("An item");
+
+        let mut doc = Document::default();
+        doc.push(list.clone())
+            .push(Equation::new("E = mc^2"))
+            .push(ParagraphElement::italic("Emphasis"));
+
+        let mut align = Align::new();
+        align.push("E = mc^2");
+        let mut para = Paragraph::new();
+        para.push(ParagraphElement::italic("Emphasis"));
+        assert_eq!(
+            *doc,
+            vec![
+                Element::List(list),
+                Element::Align(align),
+                Element::Para(para)
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_section_and_equation_numbers() {
+        let mut intro = Section::new("Introduction");
+        intro.label("sec:intro");
+
+        let mut background = Section::new("Background");
+        background.label("sec:background");
+        let mut equations = Align::new();
+        equations
+            .push(Equation::with_label("eq:first", "a &= b"))
+            .push(Equation::new("c &= d"));
+        background.push(equations);
+        let mut history = Section::new("History");
+        history.label("sec:history");
+        background.push_section(history);
+
+        let mut doc = Document::default();
+        doc.push(intro).push(background);
+        let numbers = doc.resolve_numbers();
+
+        assert_eq!(numbers.len(), 4);
+        assert_eq!(numbers["sec:intro"], "1");
+        assert_eq!(numbers["sec:background"], "2");
+        assert_eq!(numbers["sec:history"], "2.1");
+        assert_eq!(numbers["eq:first"], "1");
+
+        doc.class = DocumentClass::Report;
+        doc.elements.insert(
+            0,
+            Element::Chapter {
+                title: "One".to_string(),
+                starred: false,
+            },
+        );
+        assert_eq!(doc.resolve_numbers()["sec:history"], "1.2.1");
+    }
+
+    #[test]
+    fn resolve_figure_numbers() {
+        let mut intro = Section::new("Introduction");
+        intro.label("sec:intro");
+        let mut method = Section::new("Method");
+        method.label("sec:method");
+        method.push(Element::from((
+            "figure",
+            vec![
+                r"\includegraphics{setup}",
+                r"\caption{Setup}\label{fig:setup}",
+            ],
+        )));
+
+        let mut doc = Document::default();
+        doc.push(intro).push(method);
+        let numbers = doc.resolve_numbers();
+
+        assert_eq!(numbers.len(), 3);
+        assert_eq!(numbers["sec:intro"], "1");
+        assert_eq!(numbers["sec:method"], "2");
+        assert_eq!(numbers["fig:setup"], "1");
+    }
+
+    #[test]
+    fn subsubsections_are_unnumbered_in_reports() {
+        let mut detail = Section::new("Detail");
+        detail.label("sec:detail");
+        let mut part = Section::new("Part");
+        part.push_section(detail);
+        let mut section = Section::new("Section");
+        section.push_section(part);
+
+        let mut doc = Document::default();
+        doc.push(section);
+        assert_eq!(doc.resolve_numbers()["sec:detail"], "1.1.1");
+
+        doc.class = DocumentClass::Report;
+        assert!(doc.resolve_numbers().is_empty());
+    }
+
+    #[test]
+    fn apply_a_template_with_geometry_and_a_fancy_header() {
+        let mut template = Template::default();
+        template
+            .preamble
+            .title("House Style")
+            .use_package("geometry")
+            .use_package("amsmath")
+            .page_style(PageStyle::Fancy(FancyHeaders {
+                rhead: Some("ACME Corp".to_string()),
+                ..Default::default()
+            }));
+        template.header.push(Element::TitlePage);
+        template.footer.push(Element::ClearPage);
+
+        let amsmath = PreambleElement::UsePackage {
+            package: "amsmath".to_string(),
+            argument: Some("intlimits".to_string()),
+        };
+        let mut doc = Document::default();
+        doc.preamble.title("Report").push(amsmath.clone());
+        doc.push("Generated content.");
+        doc.apply_template(&template);
+
+        assert_eq!(doc.preamble.title, Some("Report".to_string()));
+        assert!(doc.preamble.uses_package("geometry"));
+        assert!(doc.preamble.uses_package("fancyhdr"));
+        assert!(doc.preamble.iter().any(|item| *item == amsmath));
+        assert_eq!(doc.required_packages().unwrap().len(), 3);
+        assert_eq!(doc.len(), 3);
+        assert_eq!(doc[0], Element::TitlePage);
+        assert_eq!(doc[2], Element::ClearPage);
+
+        // applying it again doesn't repeat anything in the preamble
+        let preamble = doc.preamble.clone();
+        doc.apply_template(&template);
+        assert_eq!(doc.preamble, preamble);
+    }
+
+    #[test]
+    fn typography_defaults_bundle() {
+        let mut preamble = Preamble::default();
+        preamble.typography_defaults().typography_defaults();
+
+        let packages: Vec<_> = preamble.iter().collect();
+        assert_eq!(
+            packages,
+            vec![
+                &PreambleElement::UsePackage {
+                    package: "fontenc".to_string(),
+                    argument: Some("T1".to_string()),
+                },
+                &PreambleElement::UsePackage {
+                    package: "microtype".to_string(),
+                    argument: None,
+                },
+            ]
+        );
+
+        preamble.microtype(false);
+        assert!(!preamble.uses_package("microtype"));
+        assert!(preamble.uses_package("fontenc"));
+    }
+
+    #[test]
+    fn set_the_main_font() {
+        let mut preamble = Preamble::default();
+        preamble.main_font("TeX Gyre Pagella");
+
+        let elements: Vec<_> = preamble.iter().collect();
+        assert_eq!(
+            elements,
+            vec![
+                &PreambleElement::UsePackage {
+                    package: "fontspec".to_string(),
+                    argument: None,
+                },
+                &PreambleElement::UserDefined(r"\setmainfont{TeX Gyre Pagella}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn pdflatex_encodings() {
+        let mut preamble = Preamble::default();
+        preamble.encoding_for_engine(Engine::Pdflatex);
+
+        let elements: Vec<_> = preamble.iter().collect();
+        assert_eq!(
+            elements,
+            vec![
+                &PreambleElement::UsePackage {
+                    package: "inputenc".to_string(),
+                    argument: Some("utf8".to_string()),
+                },
+                &PreambleElement::UsePackage {
+                    package: "fontenc".to_string(),
+                    argument: Some("T1".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lualatex_encodings() {
+        let mut preamble = Preamble::default();
+        preamble
+            .encoding_for_engine(Engine::Lualatex)
+            .main_font("TeX Gyre Pagella");
+
+        assert!(preamble.uses_package("fontspec"));
+        assert!(!preamble.uses_package("inputenc"));
+        assert!(!preamble.uses_package("fontenc"));
+        assert_eq!(preamble.iter().count(), 2);
+    }
+
+    #[test]
+    fn final_mode_hides_todos() {
+        let mut doc = Document::new(DocumentClass::Article);
+        assert!(!doc.todos_hidden());
+
+        doc.set_mode(DocumentMode::Draft);
+        assert!(!doc.todos_hidden());
+
+        doc.set_mode(DocumentMode::Final);
+        assert!(doc.todos_hidden());
+    }
+}
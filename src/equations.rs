@@ -1,4 +1,4 @@
-use std::ops::Deref;
+use std::fmt::{self, Display, Formatter};
 use std::slice::Iter;
 
 /// A single equation.
@@ -80,7 +80,7 @@ impl Equation {
 
     /// Get the equation label, if there is one.
     pub fn get_label(&self) -> Option<&str> {
-        self.label.as_ref().map(Deref::deref)
+        self.label.as_deref()
     }
 
     /// Is this equation numbered?
@@ -93,8 +93,8 @@ impl Equation {
 ///
 /// # Note
 ///
-/// Using this environment requires you to include the `amsmath` package in
-/// your preamble.
+/// Using this environment requires the `amsmath` package, which will be
+/// imported automatically when the document is rendered.
 ///
 ///
 /// # Examples
@@ -129,7 +129,7 @@ impl Align {
     }
 
     /// Iterate over each of this equations in the list.
-    pub fn iter(&self) -> Iter<Equation> {
+    pub fn iter(&self) -> Iter<'_, Equation> {
         self.items.iter()
     }
 
@@ -154,3 +154,207 @@ impl<'a> From<&'a str> for Align {
         eq
     }
 }
+
+/// How a `LongEquation` is broken across lines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LongEquationKind {
+    /// A `split` inside an `equation`, so the lines can be aligned at `&`.
+    Split,
+    /// A `multline`, with the first line on the left and the last on the
+    /// right.
+    Multline,
+}
+
+/// A single equation which is too long for one line, so it's broken over
+/// several (separated by `\\` when rendered). It gets one equation number.
+///
+/// # Note
+///
+/// Using this environment requires the `amsmath` package, which will be
+/// imported automatically when the document is rendered.
+///
+/// # Examples
+///
+/// ```rust
+/// use latex::{LongEquation, LongEquationKind};
+///
+/// let mut sum = LongEquation::new(LongEquationKind::Multline);
+/// sum.push("a + b + c + d").push("+ e + f + g");
+/// ```
+///
+/// Which would be rendered as:
+///
+/// ```tex
+/// \begin{multline}
+/// a + b + c + d \\
+/// + e + f + g
+/// \end{multline}
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct LongEquation {
+    /// How the equation is broken over lines.
+    pub kind: LongEquationKind,
+    lines: Vec<String>,
+    label: Option<String>,
+}
+
+impl LongEquation {
+    /// Create an empty equation.
+    pub fn new(kind: LongEquationKind) -> LongEquation {
+        LongEquation {
+            kind,
+            lines: Vec::new(),
+            label: None,
+        }
+    }
+
+    /// Add the next line of the equation.
+    pub fn push(&mut self, line: &str) -> &mut Self {
+        self.lines.push(line.to_string());
+        self
+    }
+
+    /// Give the equation a label.
+    pub fn label(&mut self, name: &str) -> &mut Self {
+        self.label = Some(name.to_string());
+        self
+    }
+
+    /// Get the equation label, if there is one.
+    pub fn get_label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Iterate over the lines of the equation.
+    pub fn iter(&self) -> Iter<'_, String> {
+        self.lines.iter()
+    }
+}
+
+/// The brackets drawn around a `Matrix`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MatrixStyle {
+    /// Round brackets (`pmatrix`).
+    Parens,
+    /// Square brackets (`bmatrix`).
+    Brackets,
+    /// Vertical bars, as used for determinants (`vmatrix`).
+    Bars,
+}
+
+impl MatrixStyle {
+    /// The name of the `amsmath` environment for this style.
+    fn environment(&self) -> &'static str {
+        match *self {
+            MatrixStyle::Parens => "pmatrix",
+            MatrixStyle::Brackets => "bmatrix",
+            MatrixStyle::Bars => "vmatrix",
+        }
+    }
+}
+
+/// A matrix, which can be embedded in an equation or inline math.
+///
+/// The matrix is rendered using its `Display` impl, and uses one of the
+/// matrix environments from the `amsmath` package. The package is imported
+/// automatically when the matrix is used in inline math or an `Align`.
+///
+/// ```rust
+/// use latex::{Matrix, MatrixStyle};
+///
+/// let identity = Matrix::new(
+///     vec![vec!["1".into(), "0".into()], vec!["0".into(), "1".into()]],
+///     MatrixStyle::Brackets,
+/// );
+/// assert_eq!(
+///     identity.to_string(),
+///     r"\begin{bmatrix} 1 & 0 \\ 0 & 1 \end{bmatrix}"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix {
+    rows: Vec<Vec<String>>,
+    style: MatrixStyle,
+}
+
+impl Matrix {
+    /// Create a matrix from its rows.
+    pub fn new(rows: Vec<Vec<String>>, style: MatrixStyle) -> Matrix {
+        Matrix { rows, style }
+    }
+
+    /// Iterate over the rows of the matrix.
+    pub fn iter(&self) -> Iter<'_, Vec<String>> {
+        self.rows.iter()
+    }
+}
+
+impl Display for Matrix {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let rows: Vec<_> = self.rows.iter().map(|row| row.join(" & ")).collect();
+        let env = self.style.environment();
+
+        write!(
+            f,
+            r"\begin{{{}}} {} \end{{{}}}",
+            env,
+            rows.join(r" \\ "),
+            env
+        )
+    }
+}
+
+/// A piecewise definition using the `cases` environment from `amsmath`, made
+/// up of `(expression, condition)` pairs.
+///
+/// Like a `Matrix`, this is rendered using its `Display` impl so it can be
+/// embedded in an equation or inline math.
+///
+/// ```rust
+/// use latex::{Cases, Equation};
+///
+/// let mut abs = Cases::new();
+/// abs.push("x", r"x \geq 0").push("-x", "x < 0");
+///
+/// let eq = Equation::new(format!("|x| = {}", abs));
+/// assert_eq!(
+///     eq.get_text(),
+///     r"|x| = \begin{cases} x & x \geq 0 \\ -x & x < 0 \end{cases}"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Cases {
+    cases: Vec<(String, String)>,
+}
+
+impl Cases {
+    /// Create an empty piecewise definition.
+    pub fn new() -> Cases {
+        Default::default()
+    }
+
+    /// Add a case, consisting of an expression and the condition under which
+    /// it applies.
+    pub fn push(&mut self, expression: &str, condition: &str) -> &mut Self {
+        self.cases
+            .push((expression.to_string(), condition.to_string()));
+        self
+    }
+
+    /// Iterate over each `(expression, condition)` pair.
+    pub fn iter(&self) -> Iter<'_, (String, String)> {
+        self.cases.iter()
+    }
+}
+
+impl Display for Cases {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let cases: Vec<_> = self
+            .cases
+            .iter()
+            .map(|(expression, condition)| format!("{} & {}", expression, condition))
+            .collect();
+
+        write!(f, r"\begin{{cases}} {} \end{{cases}}", cases.join(r" \\ "))
+    }
+}
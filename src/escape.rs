@@ -0,0 +1,39 @@
+/// Escape the characters which have a special meaning to LaTeX, so that some
+/// text (e.g. a file name or identifier) is rendered exactly as written.
+///
+/// ```rust
+/// assert_eq!(latex::escape("my_file.rs"), r"my\_file.rs");
+/// assert_eq!(latex::escape("50% of $10"), r"50\% of \$10");
+/// ```
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '_' | '$' | '%' | '&' | '#' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\\' => escaped.push_str(r"\textbackslash{}"),
+            '~' => escaped.push_str(r"\textasciitilde{}"),
+            '^' => escaped.push_str(r"\textasciicircum{}"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_every_special_character() {
+        assert_eq!(
+            escape(r"a_b$c%d&e#f{g}h\i~j^k"),
+            r"a\_b\$c\%d\&e\#f\{g\}h\textbackslash{}i\textasciitilde{}j\textasciicircum{}k"
+        );
+        assert_eq!(escape("plain text"), "plain text");
+    }
+}
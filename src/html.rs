@@ -0,0 +1,179 @@
+//! A very small converter for the inline HTML which often turns up in
+//! content from a CMS.
+
+use escape::escape;
+use paragraph::ParagraphElement;
+
+/// The inline tags which are understood by `from_simple_html()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Tag {
+    Bold,
+    Italic,
+    Code,
+}
+
+impl Tag {
+    fn from_name(name: &str) -> Option<Tag> {
+        match name.to_ascii_lowercase().as_str() {
+            "b" | "strong" => Some(Tag::Bold),
+            "i" | "em" => Some(Tag::Italic),
+            "code" => Some(Tag::Code),
+            _ => None,
+        }
+    }
+}
+
+/// Convert a snippet of simple HTML into `ParagraphElement`s.
+///
+/// `<b>`/`<strong>` become bold, `<i>`/`<em>` become italics and `<code>`
+/// becomes `ParagraphElement::Code`. Any other tags are stripped, keeping
+/// their contents, and the text is escaped so it renders as written. The
+/// `&amp;`, `&lt;`, `&gt;`, `&quot;` and `&#39;` entities are decoded.
+///
+/// ```rust
+/// use latex::{from_simple_html, ParagraphElement};
+///
+/// let elements = from_simple_html("Hello <b>world</b>");
+/// assert_eq!(
+///     elements,
+///     vec![
+///         ParagraphElement::Plain("Hello ".to_string()),
+///         ParagraphElement::bold("world"),
+///     ]
+/// );
+/// ```
+pub fn from_simple_html(html: &str) -> Vec<ParagraphElement> {
+    let mut elements = Vec::new();
+    let mut open_tags = Vec::new();
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        match find_tag(rest) {
+            Some(0) => {
+                let end = match rest.find('>') {
+                    Some(end) => end,
+                    // not a real tag, so treat the rest as text
+                    None => {
+                        push_text(&mut elements, &open_tags, rest);
+                        break;
+                    }
+                };
+
+                let tag = &rest[1..end];
+                rest = &rest[end + 1..];
+
+                if let Some(name) = tag.strip_prefix('/') {
+                    // close the matching tag, and anything left open inside it
+                    if let Some(tag) = Tag::from_name(name.trim()) {
+                        if let Some(position) = open_tags.iter().rposition(|&t| t == tag) {
+                            open_tags.truncate(position);
+                        }
+                    }
+                } else if !tag.ends_with('/') {
+                    let name = tag.split_whitespace().next().unwrap_or("");
+                    if let Some(tag) = Tag::from_name(name) {
+                        open_tags.push(tag);
+                    }
+                }
+            }
+            Some(start) => {
+                push_text(&mut elements, &open_tags, &rest[..start]);
+                rest = &rest[start..];
+            }
+            None => {
+                push_text(&mut elements, &open_tags, rest);
+                break;
+            }
+        }
+    }
+
+    elements
+}
+
+/// Find the start of the next tag, skipping any `<` which isn't followed by
+/// a tag name or `/` (e.g. in `a < b`), since that's just text.
+fn find_tag(text: &str) -> Option<usize> {
+    text.match_indices('<')
+        .map(|(start, _)| start)
+        .find(|&start| match text[start + 1..].chars().next() {
+            Some(c) => c.is_ascii_alphabetic() || c == '/',
+            None => false,
+        })
+}
+
+/// Add some text, wrapped in whichever tags are currently open.
+fn push_text(elements: &mut Vec<ParagraphElement>, open_tags: &[Tag], text: &str) {
+    if text.is_empty() {
+        return;
+    }
+
+    let text = decode_entities(text);
+    let mut element = if open_tags.contains(&Tag::Code) {
+        ParagraphElement::Code(text)
+    } else {
+        ParagraphElement::Plain(escape(&text))
+    };
+
+    for tag in open_tags.iter().rev() {
+        element = match *tag {
+            Tag::Bold => ParagraphElement::bold(element),
+            Tag::Italic => ParagraphElement::italic(element),
+            Tag::Code => element,
+        };
+    }
+
+    elements.push(element);
+}
+
+/// Decode the handful of HTML entities which are likely to appear in simple
+/// markup.
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bold_and_code_tags() {
+        let should_be = vec![
+            ParagraphElement::Plain("Hello ".to_string()),
+            ParagraphElement::bold("world"),
+            ParagraphElement::Plain(" and ".to_string()),
+            ParagraphElement::Code("x_y".to_string()),
+        ];
+
+        assert_eq!(
+            from_simple_html("Hello <b>world</b> and <code>x_y</code>"),
+            should_be
+        );
+    }
+
+    #[test]
+    fn unknown_tags_are_stripped_and_text_is_escaped() {
+        let should_be = vec![
+            ParagraphElement::Plain("Save ".to_string()),
+            ParagraphElement::italic(ParagraphElement::Plain(r"50\% \& more".to_string())),
+        ];
+
+        assert_eq!(
+            from_simple_html(r#"<span class="x">Save <em>50% &amp; more</em></span>"#),
+            should_be
+        );
+    }
+
+    #[test]
+    fn less_than_signs_are_kept_as_text() {
+        let should_be = vec![
+            ParagraphElement::Plain("if a < b then ".to_string()),
+            ParagraphElement::bold("x"),
+        ];
+
+        assert_eq!(from_simple_html("if a < b then <b>x</b>"), should_be);
+    }
+}
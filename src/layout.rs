@@ -0,0 +1,459 @@
+use std::fmt::{self, Display, Formatter};
+use std::slice::{Iter, IterMut};
+
+use document::Element;
+
+/// The style used for page headers and footers.
+///
+/// # Examples
+///
+/// The built-in LaTeX styles can be used directly, while `Fancy` lets you
+/// place your own text in each header and footer slot using the `fancyhdr`
+/// package.
+///
+/// ```rust
+/// use latex::{FancyHeaders, PageStyle, Preamble};
+///
+/// let mut preamble = Preamble::default();
+/// preamble.page_style(PageStyle::Fancy(FancyHeaders {
+///     rhead: Some("Draft".to_string()),
+///     cfoot: Some(r"\thepage".to_string()),
+///     ..Default::default()
+/// }));
+/// ```
+///
+/// Which would be rendered as:
+///
+/// ```tex
+/// \usepackage{fancyhdr}
+/// \pagestyle{fancy}
+/// \fancyhf{}
+/// \fancyhead[R]{Draft}
+/// \fancyfoot[C]{\thepage}
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum PageStyle {
+    /// Only a page number in the footer (the LaTeX default).
+    Plain,
+    /// No headers or footers at all.
+    Empty,
+    /// Running heads taken from the sectioning commands.
+    Headings,
+    /// Custom headers and footers using the `fancyhdr` package.
+    Fancy(FancyHeaders),
+}
+
+impl Display for PageStyle {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            PageStyle::Plain => write!(f, "plain"),
+            PageStyle::Empty => write!(f, "empty"),
+            PageStyle::Headings => write!(f, "headings"),
+            PageStyle::Fancy(_) => write!(f, "fancy"),
+        }
+    }
+}
+
+/// The contents of each header and footer slot of a `fancyhdr` page style.
+///
+/// Any slot left as `None` will be empty.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[allow(missing_docs)]
+pub struct FancyHeaders {
+    pub lhead: Option<String>,
+    pub chead: Option<String>,
+    pub rhead: Option<String>,
+    pub lfoot: Option<String>,
+    pub cfoot: Option<String>,
+    pub rfoot: Option<String>,
+}
+
+impl FancyHeaders {
+    /// Iterate over the header slots which have been set, as a tuple of the
+    /// `fancyhdr` command, position, and text.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str, &str)> {
+        let slots = vec![
+            ("fancyhead", "L", &self.lhead),
+            ("fancyhead", "C", &self.chead),
+            ("fancyhead", "R", &self.rhead),
+            ("fancyfoot", "L", &self.lfoot),
+            ("fancyfoot", "C", &self.cfoot),
+            ("fancyfoot", "R", &self.rfoot),
+        ];
+
+        slots
+            .into_iter()
+            .filter_map(|(cmd, pos, text)| text.as_ref().map(|t| (cmd, pos, t.as_str())))
+    }
+}
+
+/// The style of page numbers, as passed to `\pagenumbering`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NumberStyle {
+    /// 1, 2, 3, ...
+    Arabic,
+    /// i, ii, iii, ..., often used for front matter.
+    Roman,
+    /// I, II, III, ...
+    UpperRoman,
+    /// a, b, c, ...
+    Alph,
+    /// A, B, C, ...
+    UpperAlph,
+}
+
+impl Display for NumberStyle {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            NumberStyle::Arabic => write!(f, "arabic"),
+            NumberStyle::Roman => write!(f, "roman"),
+            NumberStyle::UpperRoman => write!(f, "Roman"),
+            NumberStyle::Alph => write!(f, "alph"),
+            NumberStyle::UpperAlph => write!(f, "Alph"),
+        }
+    }
+}
+
+/// The line spacing used for a document's body, set using the `setspace`
+/// package.
+///
+/// ```rust
+/// use latex::{LineSpacing, Preamble};
+///
+/// let mut preamble = Preamble::default();
+/// preamble.line_spacing(LineSpacing::Double);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineSpacing {
+    /// Normal, single spacing.
+    Single,
+    /// One and a half spacing.
+    OneHalf,
+    /// Double spacing, often wanted for drafts which will be marked up.
+    Double,
+    /// Stretch the baseline by a custom factor with `\setstretch`.
+    Custom(f64),
+}
+
+impl Display for LineSpacing {
+    /// Render the `setspace` command for this spacing.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            LineSpacing::Single => write!(f, r"\singlespacing"),
+            LineSpacing::OneHalf => write!(f, r"\onehalfspacing"),
+            LineSpacing::Double => write!(f, r"\doublespacing"),
+            LineSpacing::Custom(stretch) => write!(f, r"\setstretch{{{}}}", stretch),
+        }
+    }
+}
+
+/// Elements which are set on landscape pages using the `pdflscape` package,
+/// which is imported automatically when the document is rendered. This is
+/// useful for wide tables and figures.
+///
+/// ```rust
+/// use latex::{Element, Landscape};
+///
+/// let mut landscape = Landscape::new();
+/// landscape.push(Element::UserDefined(r"\includegraphics{wide.png}".to_string()));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Landscape {
+    elements: Vec<Element>,
+}
+
+impl Landscape {
+    /// Create an empty landscape section.
+    pub fn new() -> Landscape {
+        Default::default()
+    }
+
+    /// Add an element to be rotated.
+    pub fn push<I>(&mut self, element: I) -> &mut Self
+    where
+        I: Into<Element>,
+    {
+        self.elements.push(element.into());
+        self
+    }
+
+    /// Iterate over the elements in this landscape section.
+    pub fn iter(&self) -> Iter<'_, Element> {
+        self.elements.iter()
+    }
+
+    /// Mutably iterate over the elements in this landscape section.
+    pub fn iter_mut(&mut self) -> IterMut<'_, Element> {
+        self.elements.iter_mut()
+    }
+}
+
+/// Elements which are shrunk to fit within a maximum width (if they're
+/// wider), using `\adjustbox` from the `adjustbox` package. The package is
+/// imported automatically when the document is rendered.
+///
+/// ```rust
+/// use latex::{AdjustBox, Element};
+///
+/// let mut wide = AdjustBox::new(r"\textwidth");
+/// wide.push(Element::UserDefined(r"\includegraphics{panorama.png}".to_string()));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AdjustBox {
+    /// The widest the contents may be (e.g. `\textwidth`).
+    pub max_width: String,
+    elements: Vec<Element>,
+}
+
+impl AdjustBox {
+    /// Create an empty box with the provided maximum width.
+    pub fn new(max_width: &str) -> AdjustBox {
+        AdjustBox {
+            max_width: max_width.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Add an element to the box.
+    pub fn push<I>(&mut self, element: I) -> &mut Self
+    where
+        I: Into<Element>,
+    {
+        self.elements.push(element.into());
+        self
+    }
+
+    /// Iterate over the elements in the box.
+    pub fn iter(&self) -> Iter<'_, Element> {
+        self.elements.iter()
+    }
+
+    /// Mutably iterate over the elements in the box.
+    pub fn iter_mut(&mut self) -> IterMut<'_, Element> {
+        self.elements.iter_mut()
+    }
+}
+
+/// Which side of the page a `WrapFigure` sits on.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum WrapSide {
+    /// The figure is on the left, with text flowing down its right.
+    Left,
+    /// The figure is on the right, with text flowing down its left.
+    #[default]
+    Right,
+}
+
+impl Display for WrapSide {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            WrapSide::Left => write!(f, "l"),
+            WrapSide::Right => write!(f, "r"),
+        }
+    }
+}
+
+/// A figure which the surrounding text flows around, using the `wrapfig`
+/// package. The package is imported automatically when the document is
+/// rendered.
+///
+/// ```rust
+/// use latex::{Element, WrapFigure, WrapSide};
+///
+/// let mut figure = WrapFigure::new(WrapSide::Right, r"0.4\textwidth");
+/// figure.push(Element::UserDefined(r"\includegraphics{logo.png}".to_string()));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WrapFigure {
+    /// Which side of the text the figure goes on.
+    pub side: WrapSide,
+    /// How wide the figure is (e.g. `0.4\textwidth`).
+    pub width: String,
+    elements: Vec<Element>,
+}
+
+impl WrapFigure {
+    /// Create an empty figure on one side of the page.
+    pub fn new(side: WrapSide, width: &str) -> WrapFigure {
+        WrapFigure {
+            side,
+            width: width.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Add an element to the figure.
+    pub fn push<I>(&mut self, element: I) -> &mut Self
+    where
+        I: Into<Element>,
+    {
+        self.elements.push(element.into());
+        self
+    }
+
+    /// Iterate over the elements in the figure.
+    pub fn iter(&self) -> Iter<'_, Element> {
+        self.elements.iter()
+    }
+
+    /// Mutably iterate over the elements in the figure.
+    pub fn iter_mut(&mut self) -> IterMut<'_, Element> {
+        self.elements.iter_mut()
+    }
+}
+
+/// How the contents of an `Aligned` block are lined up.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Alignment {
+    /// Centred, with the `center` environment.
+    #[default]
+    Centered,
+    /// Against the left margin, with the `flushleft` environment.
+    FlushLeft,
+    /// Against the right margin, with the `flushright` environment.
+    FlushRight,
+}
+
+impl Display for Alignment {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Alignment::Centered => write!(f, "center"),
+            Alignment::FlushLeft => write!(f, "flushleft"),
+            Alignment::FlushRight => write!(f, "flushright"),
+        }
+    }
+}
+
+/// Some elements which are centred, or pushed against one of the margins.
+///
+/// ```rust
+/// use latex::{Aligned, Alignment};
+///
+/// let mut signature = Aligned::new(Alignment::FlushRight);
+/// signature.push("Yours faithfully,");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Aligned {
+    /// How the elements are lined up.
+    pub alignment: Alignment,
+    elements: Vec<Element>,
+}
+
+impl Aligned {
+    /// Create an empty block with the provided alignment.
+    pub fn new(alignment: Alignment) -> Aligned {
+        Aligned {
+            alignment,
+            ..Default::default()
+        }
+    }
+
+    /// Add an element to the block.
+    pub fn push<I>(&mut self, element: I) -> &mut Self
+    where
+        I: Into<Element>,
+    {
+        self.elements.push(element.into());
+        self
+    }
+
+    /// Iterate over the elements in the block.
+    pub fn iter(&self) -> Iter<'_, Element> {
+        self.elements.iter()
+    }
+
+    /// Mutably iterate over the elements in the block.
+    pub fn iter_mut(&mut self) -> IterMut<'_, Element> {
+        self.elements.iter_mut()
+    }
+}
+
+/// A coloured callout box (e.g. for tips and warnings), using the
+/// `tcolorbox` package. The package is imported automatically when the
+/// document is rendered.
+///
+/// ```rust
+/// use latex::ColorBox;
+///
+/// let mut tip = ColorBox::new();
+/// tip.title("Tip").color("green!5");
+/// tip.push("Save early, save often.");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ColorBox {
+    title: Option<String>,
+    color: Option<String>,
+    elements: Vec<Element>,
+}
+
+impl ColorBox {
+    /// Create an empty box using `tcolorbox`'s default style.
+    pub fn new() -> ColorBox {
+        Default::default()
+    }
+
+    /// Set the title shown in the box's header.
+    pub fn title(&mut self, title: &str) -> &mut Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Set the background colour (e.g. `red!5`).
+    pub fn color(&mut self, color: &str) -> &mut Self {
+        self.color = Some(color.to_string());
+        self
+    }
+
+    /// The options passed to the `tcolorbox` environment, if there are any.
+    pub fn options(&self) -> String {
+        let mut options = Vec::new();
+        if let Some(ref title) = self.title {
+            options.push(format!("title={{{}}}", title));
+        }
+        if let Some(ref color) = self.color {
+            options.push(format!("colback={}", color));
+        }
+
+        options.join(",")
+    }
+
+    /// Add an element to the box.
+    pub fn push<I>(&mut self, element: I) -> &mut Self
+    where
+        I: Into<Element>,
+    {
+        self.elements.push(element.into());
+        self
+    }
+
+    /// Iterate over the elements in the box.
+    pub fn iter(&self) -> Iter<'_, Element> {
+        self.elements.iter()
+    }
+
+    /// Mutably iterate over the elements in the box.
+    pub fn iter_mut(&mut self) -> IterMut<'_, Element> {
+        self.elements.iter_mut()
+    }
+}
+
+/// Tweaks to how the table of contents is laid out, using the `tocloft`
+/// package. See `Preamble::toc_style()`.
+///
+/// ```rust
+/// use latex::{Preamble, TocStyle};
+///
+/// let mut preamble = Preamble::default();
+/// preamble.toc_style(TocStyle {
+///     dot_separation: Some("2".to_string()),
+///     section_dots: true,
+/// });
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TocStyle {
+    /// How far apart the dots in the leaders are (`\cftdotsep`, in `mu`).
+    pub dot_separation: Option<String>,
+    /// Draw dot leaders for sections too, rather than only for the
+    /// subsections beneath them.
+    pub section_dots: bool,
+}
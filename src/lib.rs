@@ -104,19 +104,61 @@
 
 #![deny(missing_docs)]
 
+#[macro_use]
 extern crate failure;
 
+mod acronyms;
+mod bibliography;
+mod code;
+mod csv_table;
 mod document;
 mod equations;
+mod escape;
+mod html;
+mod layout;
 mod lists;
+mod markdown;
 mod paragraph;
+mod plot;
+mod proof;
+mod references;
 mod section;
+mod template;
+mod three_part_table;
+mod title_page;
 mod visitor;
 
-pub use document::{Document, DocumentClass, Element, Preamble, PreambleElement};
-pub use equations::{Align, Equation};
+pub use acronyms::AcronymList;
+pub use bibliography::{
+    classic_commands, classic_commands_multi, validate_key, BibBackend, BibStyle, BiblatexOptions,
+    CiteScheme, ManualBibliography, PrintBibOptions,
+};
+pub use code::MintedOptions;
+pub use csv_table::CsvTable;
+pub use document::{
+    Document, DocumentClass, DocumentMode, Element, Engine, Package, Preamble, PreambleElement,
+};
+pub use equations::{Align, Cases, Equation, LongEquation, LongEquationKind, Matrix, MatrixStyle};
+pub use escape::escape;
+pub use html::from_simple_html;
+pub use layout::{
+    AdjustBox, Aligned, Alignment, ColorBox, FancyHeaders, Landscape, LineSpacing, NumberStyle,
+    PageStyle, TocStyle, WrapFigure, WrapSide,
+};
 pub use lists::{Item, List, ListKind};
+pub use markdown::from_markdown;
 pub use paragraph::{Paragraph, ParagraphElement};
+pub use plot::Plot;
+pub use proof::Proof;
+pub use references::{
+    auto_reference, cref, cref_upper, doi_link, mailto, name_reference, sanitize_label, HyperSetup,
+};
 pub use section::Section;
+pub use template::Template;
+pub use three_part_table::ThreePartTable;
+pub use title_page::{Abstract, Keywords, TitlePageBuilder};
 
-pub use visitor::{print, Visitor};
+pub use visitor::{
+    normalize_latex, preview, print, print_with, validate, write_document, Diagnostic,
+    ElementSummary, LineEnding, RenderError, RenderOptions, Visitor,
+};
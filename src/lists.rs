@@ -1,6 +1,8 @@
 use std::ops::Deref;
 use std::slice::Iter;
 
+use failure::Error;
+
 /// Wrapper around a single list item.
 ///
 /// An item will usually be rendered with `\item` followed by the item's text.
@@ -33,6 +35,9 @@ impl ListKind {
     }
 }
 
+/// The counter commands `enumitem` accepts in a numbered list's label.
+const LABEL_COUNTERS: &[&str] = &[r"\arabic*", r"\alph*", r"\Alph*", r"\roman*", r"\Roman*"];
+
 /// A list (either dot points or numbered).
 ///
 /// # Examples
@@ -57,11 +62,40 @@ impl ListKind {
 /// \item Dot-points
 /// \end{itemize}
 /// ```
+///
+/// You can also customise how each item is labelled using the `enumitem`
+/// package.
+///
+/// ```rust
+/// use latex::{List, ListKind};
+///
+/// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut list = List::new(ListKind::Enumerate);
+/// list.label_format(r"(\alph*)")?.push("First").push("Second");
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// # run().unwrap();
+/// # }
+/// ```
+///
+/// Which renders as:
+///
+/// ```tex
+/// \begin{enumerate}[label={(\alph*)}]
+/// \item First
+/// \item Second
+/// \end{enumerate}
+/// ```
 #[derive(Clone, Debug, PartialEq)]
 pub struct List {
     /// The kind of list this is.
     pub kind: ListKind,
     items: Vec<Item>,
+    overlays: Vec<Option<String>>,
+    label_format: Option<String>,
+    compact: bool,
+    resume: bool,
 }
 
 impl List {
@@ -70,25 +104,134 @@ impl List {
         List {
             kind,
             items: Vec::new(),
+            overlays: Vec::new(),
+            label_format: None,
+            compact: false,
+            resume: false,
+        }
+    }
+
+    /// Set the label used for each item (e.g. `(\alph*)` or `\arabic*.`).
+    ///
+    /// The format must have balanced braces, and a numbered list's format
+    /// must contain one of the `enumitem` counters (`\arabic*`, `\alph*`,
+    /// `\Alph*`, `\roman*`, or `\Roman*`).
+    pub fn label_format(&mut self, format: &str) -> Result<&mut Self, Error> {
+        if format.is_empty() || !braces_are_balanced(format) {
+            bail!("\"{}\" is not a valid list label", format);
+        }
+        if self.kind == ListKind::Enumerate && !LABEL_COUNTERS.iter().any(|c| format.contains(c)) {
+            bail!(
+                "The numbered list label \"{}\" doesn't contain a counter",
+                format
+            );
+        }
+
+        self.label_format = Some(format.to_string());
+        Ok(self)
+    }
+
+    /// Set whether the list should be compact, removing the extra space
+    /// LaTeX normally puts between items and around the list.
+    pub fn compact(&mut self, compact: bool) -> &mut Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Set whether a numbered list should carry on counting from the end of
+    /// the previous one, rather than starting again at 1.
+    pub fn resume(&mut self, resume: bool) -> &mut Self {
+        self.resume = resume;
+        self
+    }
+
+    /// Get the options to pass to the list's environment, if any.
+    pub fn options(&self) -> Vec<String> {
+        let mut options = Vec::new();
+
+        if let Some(ref format) = self.label_format {
+            options.push(format!("label={{{}}}", format));
+        }
+        if self.compact {
+            options.push("noitemsep".to_string());
+            options.push("topsep=0pt".to_string());
+        }
+        if self.resume {
+            options.push("resume".to_string());
         }
+
+        options
     }
 
     /// Add an element to the list.
     pub fn push<S: AsRef<str>>(&mut self, item: S) -> &mut Self {
         self.items.push(Item(item.as_ref().to_string()));
+        self.overlays.push(None);
         self
     }
 
+    /// Add an element to the list which is only revealed on certain slides
+    /// of a beamer frame, rendering as e.g. `\item<2->{...}`.
+    pub fn push_with_overlay<S: AsRef<str>>(&mut self, item: S, overlay: &str) -> &mut Self {
+        self.items.push(Item(item.as_ref().to_string()));
+        self.overlays.push(Some(overlay.to_string()));
+        self
+    }
+
+    /// Get the beamer overlay specification (e.g. `2-`) of the item at an
+    /// index, if it has one.
+    pub fn overlay(&self, index: usize) -> Option<&str> {
+        self.overlays
+            .get(index)
+            .and_then(|overlay| overlay.as_deref())
+    }
+
     /// Iterate over the items in the list.
-    pub fn iter(&self) -> Iter<Item> {
+    pub fn iter(&self) -> Iter<'_, Item> {
         self.items.iter()
     }
 }
 
+/// Check every `{` in some text has a matching `}`.
+fn braces_are_balanced(text: &str) -> bool {
+    let mut depth = 0_usize;
+
+    for c in text.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return false,
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    depth == 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn numbered_labels_need_a_counter() {
+        let mut list = List::new(ListKind::Enumerate);
+
+        assert!(list.label_format("(a)").is_err());
+        assert!(list.label_format(r"(\alph*)").is_ok());
+        assert!(list.label_format(r"\textbf{\arabic*").is_err());
+    }
+
+    #[test]
+    fn label_formats_are_braced() {
+        let mut list = List::new(ListKind::Enumerate);
+
+        // brackets and commas would otherwise end the option early
+        list.label_format(r"[\arabic*]").unwrap();
+        assert_eq!(list.options(), vec![r"label={[\arabic*]}"]);
+        list.label_format(r"\arabic*,").unwrap();
+        assert_eq!(list.options(), vec![r"label={\arabic*,}"]);
+    }
+
     #[test]
     fn push_item_to_list() {
         let mut list = List::new(ListKind::Itemize);
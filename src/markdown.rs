@@ -0,0 +1,328 @@
+//! Import a small subset of Markdown as document `Element`s.
+//!
+//! Only the following is supported:
+//!
+//! - ATX headings (`#`, `##`, ...), which become nested `Section`s
+//! - paragraphs, separated by blank lines
+//! - `**bold**`, `*emphasis*` (or `_emphasis_`) and `` `code` `` inline
+//! - bullet lists, where each item is a single line starting with `-`, `*` or
+//!   `+`
+//! - fenced code blocks, which become `lstlisting` environments from the
+//!   `listings` package
+//!
+//! Anything else (links, images, tables, block quotes, numbered lists, ...)
+//! is treated as plain text. Plain text is escaped, so it renders as written.
+
+use document::Element;
+use escape::escape;
+use lists::{List, ListKind};
+use paragraph::{Paragraph, ParagraphElement};
+use section::Section;
+use visitor::{Printer, Visitor};
+
+/// Convert some Markdown into `Element`s. See the module docs for exactly
+/// which syntax is understood.
+///
+/// ```rust
+/// use latex::{from_markdown, print, Document};
+///
+/// let mut doc = Document::default();
+/// doc.push_all(from_markdown("# Introduction\n\nSome **bold** text."));
+/// let rendered = print(&doc).unwrap();
+///
+/// assert!(rendered.contains("\\section{Introduction}"));
+/// assert!(rendered.contains("Some \\textbf{bold} text."));
+/// ```
+pub fn from_markdown(markdown: &str) -> Vec<Element> {
+    let mut builder = Builder::default();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut list: Option<List> = None;
+    let mut lines = markdown.lines();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") {
+            builder.finish_block(&mut paragraph, &mut list);
+            let code: Vec<String> = lines
+                .by_ref()
+                .take_while(|l| !l.trim().starts_with("```"))
+                .map(ToString::to_string)
+                .collect();
+            builder.push(Element::Environment("lstlisting".to_string(), code));
+        } else if let Some((level, title)) = heading(trimmed) {
+            builder.finish_block(&mut paragraph, &mut list);
+            builder.open_section(level, title);
+        } else if let Some(item) = bullet(trimmed) {
+            if !paragraph.is_empty() {
+                builder.finish_block(&mut paragraph, &mut None);
+            }
+            list.get_or_insert_with(|| List::new(ListKind::Itemize))
+                .push(inline_text(item));
+        } else if trimmed.is_empty() {
+            builder.finish_block(&mut paragraph, &mut list);
+        } else {
+            if list.is_some() {
+                builder.finish_block(&mut Vec::new(), &mut list);
+            }
+            paragraph.push(trimmed);
+        }
+    }
+
+    builder.finish_block(&mut paragraph, &mut list);
+    builder.finish()
+}
+
+/// Keeps track of the sections which are currently open.
+#[derive(Debug, Default)]
+struct Builder {
+    elements: Vec<Element>,
+    sections: Vec<(usize, Section)>,
+}
+
+impl Builder {
+    fn push(&mut self, element: Element) {
+        match self.sections.last_mut() {
+            Some(&mut (_, ref mut section)) => {
+                section.push(element);
+            }
+            None => self.elements.push(element),
+        }
+    }
+
+    /// Add any paragraph or list which is in progress.
+    fn finish_block(&mut self, paragraph: &mut Vec<&str>, list: &mut Option<List>) {
+        if !paragraph.is_empty() {
+            let mut para = Paragraph::new();
+            for element in inline(&paragraph.join(" ")) {
+                para.push(element);
+            }
+            self.push(Element::Para(para));
+            paragraph.clear();
+        }
+
+        if let Some(list) = list.take() {
+            self.push(Element::List(list));
+        }
+    }
+
+    /// Start a new section, closing any open sections at the same level or
+    /// deeper.
+    fn open_section(&mut self, level: usize, title: &str) {
+        self.close_sections(level);
+        self.sections.push((level, Section::new(&escape(title))));
+    }
+
+    fn close_sections(&mut self, level: usize) {
+        while self.sections.last().is_some_and(|&(l, _)| l >= level) {
+            let (_, section) = self.sections.pop().unwrap();
+            self.push(Element::Section(section));
+        }
+    }
+
+    fn finish(mut self) -> Vec<Element> {
+        self.close_sections(0);
+        self.elements
+    }
+}
+
+/// Parse an ATX heading into its level and title.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+
+    if level == 0 || level > 6 {
+        return None;
+    }
+
+    let rest = &line[level..];
+    if rest.is_empty() || rest.starts_with(' ') {
+        Some((level, rest.trim().trim_end_matches('#').trim()))
+    } else {
+        None
+    }
+}
+
+/// Get the text of a bullet list item.
+fn bullet(line: &str) -> Option<&str> {
+    ["- ", "* ", "+ "]
+        .iter()
+        .find(|marker| line.starts_with(**marker))
+        .map(|marker| line[marker.len()..].trim())
+}
+
+/// Parse inline markup into `ParagraphElement`s.
+fn inline(text: &str) -> Vec<ParagraphElement> {
+    let mut elements = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let previous = text[..text.len() - rest.len()].chars().next_back();
+
+        // "**" has to be checked before "*"
+        let span = ["**", "`", "*", "_"]
+            .iter()
+            .filter(|&&marker| rest.starts_with(marker))
+            // like CommonMark, "_" only opens emphasis at the start of a word
+            .filter(|&&marker| marker != "_" || !previous.is_some_and(char::is_alphanumeric))
+            // and emphasis can't start with a space, so "2 * 3 * 4" is plain
+            .filter(|&&marker| {
+                marker == "`"
+                    || !rest[marker.len()..]
+                        .chars()
+                        .next()
+                        .is_some_and(char::is_whitespace)
+            })
+            .filter_map(|&marker| {
+                closing_marker(&rest[marker.len()..], marker)
+                    .filter(|&end| end > 0)
+                    .map(|end| (marker, &rest[marker.len()..marker.len() + end]))
+            })
+            .next();
+
+        match span {
+            Some((marker, inner)) => {
+                if !plain.is_empty() {
+                    elements.push(ParagraphElement::Plain(escape(&plain)));
+                    plain.clear();
+                }
+
+                elements.push(match marker {
+                    "**" => ParagraphElement::bold(ParagraphElement::Plain(escape(inner))),
+                    "`" => ParagraphElement::Code(inner.to_string()),
+                    _ => ParagraphElement::italic(ParagraphElement::Plain(escape(inner))),
+                });
+                rest = &rest[2 * marker.len() + inner.len()..];
+            }
+            None => {
+                let c = rest.chars().next().unwrap();
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    if !plain.is_empty() {
+        elements.push(ParagraphElement::Plain(escape(&plain)));
+    }
+
+    elements
+}
+
+/// Find the marker which closes a span. A closing "_" has to be at the end of
+/// a word, so the underscores in `snake_case_names` are left alone, and
+/// emphasis can't end with a space.
+fn closing_marker(text: &str, marker: &str) -> Option<usize> {
+    text.match_indices(marker).map(|(end, _)| end).find(|&end| {
+        let at_word_end = marker != "_"
+            || !text[end + 1..]
+                .chars()
+                .next()
+                .is_some_and(char::is_alphanumeric);
+        let after_space = text[..end]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_whitespace);
+
+        at_word_end && (marker == "`" || !after_space)
+    })
+}
+
+/// Render inline markup straight to LaTeX, for use in list items.
+fn inline_text(text: &str) -> String {
+    let mut buffer = Vec::new();
+    {
+        let mut printer = Printer::new(&mut buffer);
+        for element in inline(text) {
+            printer
+                .visit_paragraph_element(&element)
+                .expect("writing to a Vec never fails");
+        }
+    }
+
+    String::from_utf8(buffer).expect("the printer always writes UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heading_list_and_code_fence() {
+        let markdown = "# Setup
+
+Install the *latest* version:
+
+- Download `my_tool`
+- Run **it**
+
+```
+cargo install my_tool
+```
+";
+        let mut list = List::new(ListKind::Itemize);
+        list.push(r"Download \texttt{my\_tool}")
+            .push(r"Run \textbf{it}");
+
+        let mut para = Paragraph::new();
+        para.push("Install the ")
+            .push(ParagraphElement::italic("latest"))
+            .push(" version:");
+
+        let mut section = Section::new("Setup");
+        section.push(para).push(list).push(Element::Environment(
+            "lstlisting".to_string(),
+            vec!["cargo install my_tool".to_string()],
+        ));
+
+        assert_eq!(from_markdown(markdown), vec![Element::Section(section)]);
+    }
+
+    #[test]
+    fn nested_headings_become_nested_sections() {
+        let mut child = Section::new("Child");
+        child.push("Inner.");
+        let mut parent = Section::new("Parent");
+        parent.push_section(child);
+        let sibling = Section::new("Sibling");
+
+        assert_eq!(
+            from_markdown("# Parent\n## Child\nInner.\n# Sibling"),
+            vec![Element::Section(parent), Element::Section(sibling)]
+        );
+    }
+
+    #[test]
+    fn underscores_inside_words_are_not_emphasis() {
+        assert_eq!(
+            inline("use snake_case_names here"),
+            vec![ParagraphElement::Plain(
+                r"use snake\_case\_names here".to_string()
+            )]
+        );
+        assert_eq!(
+            inline("an _italic_ word"),
+            vec![
+                ParagraphElement::Plain("an ".to_string()),
+                ParagraphElement::italic("italic"),
+                ParagraphElement::Plain(" word".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn spaced_out_stars_are_not_emphasis() {
+        assert_eq!(
+            inline("Compute 2 * 3 * 4"),
+            vec![ParagraphElement::Plain("Compute 2 * 3 * 4".to_string())]
+        );
+        assert_eq!(
+            inline("a *b *c* d"),
+            vec![
+                ParagraphElement::Plain("a ".to_string()),
+                ParagraphElement::italic("b *c"),
+                ParagraphElement::Plain(" d".to_string()),
+            ]
+        );
+    }
+}
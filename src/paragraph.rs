@@ -1,5 +1,7 @@
 use std::slice::Iter;
 
+use failure::Error;
+
 /// A single paragraph.
 ///
 /// # Examples
@@ -52,7 +54,7 @@ impl Paragraph {
     }
 
     /// Iterate over the `ParagraphElement`s in this `Paragraph`.
-    pub fn iter(&self) -> Iter<ParagraphElement> {
+    pub fn iter(&self) -> Iter<'_, ParagraphElement> {
         self.elements.iter()
     }
 }
@@ -77,8 +79,82 @@ pub enum ParagraphElement {
     Bold(Box<ParagraphElement>),
     /// Italicized text.
     Italic(Box<ParagraphElement>),
+    /// Text in quotation marks, using `\enquote` from the `csquotes` package.
+    /// The text is escaped when rendered.
+    ///
+    /// A quote inside the quote can be written with a pair of straight double
+    /// quotes (`"`), and `csquotes` will alternate between outer and inner
+    /// quotation marks.
+    Enquote(String),
+    /// A footnote, which is rendered as an endnote when the document uses
+    /// endnotes.
+    Footnote(Box<ParagraphElement>),
+    /// Inline verbatim text with `\verb`, using a delimiter which doesn't
+    /// appear in the text. Rendering fails if there isn't one. Since `\verb`
+    /// can't go in another command's argument, it falls back to escaped
+    /// `\texttt` inside bold, italic and footnote text.
+    Verb(String),
+    /// Underlined text, using `\uline` from the `ulem` package. The text is
+    /// escaped when rendered.
+    Underline(String),
+    /// Struck out text, using `\sout` from the `ulem` package. The text is
+    /// escaped when rendered.
+    Strikeout(String),
+    /// Highlighted text, using `\hl` from the `soul` package. The text is
+    /// escaped when rendered.
+    Highlight(String),
     /// An inline mathematical expression.
     InlineMath(String),
+    /// A use of an acronym defined in an `AcronymList`, by its key.
+    Acro(String),
+    /// Inline code or an identifier, such as a file name, rendered in a
+    /// monospace font with `\texttt`.
+    ///
+    /// The text is escaped when rendered, so characters like `_` and `$` come
+    /// out as written.
+    Code(String),
+    /// A quantity with units, rendered with `\SI` from the `siunitx` package.
+    ///
+    /// Use `ParagraphElement::si()` to make sure the value is actually a
+    /// number. Rendering fails if it isn't.
+    SiUnit {
+        /// The numeric value (e.g. `9.81`).
+        value: String,
+        /// The unit, written using `siunitx` macros (e.g. `\meter\per\second`).
+        unit: String,
+    },
+    /// A `\todo` note from the `todonotes` package, which is rendered as
+    /// nothing when a document's todos are hidden.
+    Todo(String),
+    /// A review comment which is added to the PDF as an annotation, using
+    /// `\pdfcomment` from the `pdfcomment` package. Unlike a `Todo` it isn't
+    /// printed on the page, and it's kept when todos are hidden.
+    PdfComment(String),
+    /// Add a symbol to the nomenclature (from the `nomencl` package), which
+    /// is printed wherever an `Element::PrintNomenclature` is placed.
+    Nomenclature {
+        /// The symbol, usually in maths mode (e.g. `$c$`).
+        symbol: String,
+        /// What the symbol stands for.
+        description: String,
+    },
+}
+
+/// The characters `\verb` can be delimited with, in order of preference.
+const VERB_DELIMITERS: &[char] = &['|', '!', '+', '/', '=', ':', ';', '@', '"', '\'', '~'];
+
+/// Pick a delimiter for `\verb` which doesn't appear in the text.
+pub(crate) fn verb_delimiter(text: &str) -> Option<char> {
+    VERB_DELIMITERS.iter().cloned().find(|&d| !text.contains(d))
+}
+
+/// Make sure a value for `\SI` is a finite number. Rust also parses words
+/// like "inf" and "NaN" as floats, but `siunitx` would choke on them.
+pub(crate) fn check_si_value(value: &str) -> Result<(), Error> {
+    match value.trim().parse::<f64>() {
+        Ok(number) if number.is_finite() => Ok(()),
+        _ => bail!("\"{}\" is not a number", value),
+    }
 }
 
 impl ParagraphElement {
@@ -90,6 +166,42 @@ impl ParagraphElement {
         ParagraphElement::Italic(Box::new(elem.into()))
     }
 
+    /// Convenience method for putting some text in quotation marks.
+    ///
+    /// ```rust
+    /// # use latex::ParagraphElement;
+    /// let quote = ParagraphElement::enquote(r#"She said "Hello""#);
+    /// // \enquote{She said \enquote{Hello}}
+    /// ```
+    pub fn enquote(text: &str) -> ParagraphElement {
+        ParagraphElement::Enquote(text.to_string())
+    }
+
+    /// Convenience method for turning a `ParagraphElement` into a footnote.
+    pub fn footnote<E>(elem: E) -> ParagraphElement
+    where
+        E: Into<ParagraphElement>,
+    {
+        ParagraphElement::Footnote(Box::new(elem.into()))
+    }
+
+    /// Create a quantity with units, checking that the value is a number.
+    ///
+    /// ```rust
+    /// # use latex::ParagraphElement;
+    /// assert!(ParagraphElement::si("9.81", r"\meter\per\second\squared").is_ok());
+    /// assert!(ParagraphElement::si("fast", r"\meter\per\second").is_err());
+    /// assert!(ParagraphElement::si("NaN", r"\meter\per\second").is_err());
+    /// ```
+    pub fn si(value: &str, unit: &str) -> Result<ParagraphElement, Error> {
+        check_si_value(value)?;
+
+        Ok(ParagraphElement::SiUnit {
+            value: value.trim().to_string(),
+            unit: unit.to_string(),
+        })
+    }
+
     /// Convenience method for wrapping a `ParagraphElement` in a bold tag.
     pub fn bold<E>(elem: E) -> ParagraphElement
     where
@@ -0,0 +1,65 @@
+use std::slice::Iter;
+
+/// A line plot of some data points, drawn using the `pgfplots` package.
+///
+/// # Examples
+///
+/// ```rust
+/// use latex::Plot;
+///
+/// let mut plot = Plot::new(vec![(0.0, 0.0), (1.0, 1.0), (2.0, 4.0)]);
+/// plot.x_label("Time (s)").y_label("Distance (m)");
+/// ```
+///
+/// Which would be rendered as:
+///
+/// ```tex
+/// \begin{tikzpicture}
+/// \begin{axis}[xlabel={Time (s)}, ylabel={Distance (m)}]
+/// \addplot coordinates {(0,0) (1,1) (2,4)};
+/// \end{axis}
+/// \end{tikzpicture}
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Plot {
+    points: Vec<(f64, f64)>,
+    x_label: Option<String>,
+    y_label: Option<String>,
+}
+
+impl Plot {
+    /// Create a new plot of the provided `(x, y)` points.
+    pub fn new(points: Vec<(f64, f64)>) -> Plot {
+        Plot {
+            points,
+            ..Default::default()
+        }
+    }
+
+    /// Set the label for the x axis.
+    pub fn x_label(&mut self, label: &str) -> &mut Self {
+        self.x_label = Some(label.to_string());
+        self
+    }
+
+    /// Set the label for the y axis.
+    pub fn y_label(&mut self, label: &str) -> &mut Self {
+        self.y_label = Some(label.to_string());
+        self
+    }
+
+    /// Get the label for the x axis, if there is one.
+    pub fn get_x_label(&self) -> Option<&str> {
+        self.x_label.as_deref()
+    }
+
+    /// Get the label for the y axis, if there is one.
+    pub fn get_y_label(&self) -> Option<&str> {
+        self.y_label.as_deref()
+    }
+
+    /// Iterate over the plot's data points.
+    pub fn iter(&self) -> Iter<'_, (f64, f64)> {
+        self.points.iter()
+    }
+}
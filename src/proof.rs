@@ -0,0 +1,69 @@
+use std::slice::{Iter, IterMut};
+
+use document::Element;
+
+/// A `proof` environment from the `amsthm` package, which is imported
+/// automatically when the document is rendered.
+///
+/// The proof starts with "Proof." unless it is given a custom leading phrase,
+/// and ends with a QED symbol.
+///
+/// ```rust
+/// use latex::Proof;
+///
+/// let mut proof = Proof::with_phrase("Proof of the main theorem");
+/// proof.push("Obvious.");
+/// ```
+///
+/// Which would be rendered as:
+///
+/// ```tex
+/// \begin{proof}[Proof of the main theorem]
+/// Obvious.
+/// \end{proof}
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Proof {
+    phrase: Option<String>,
+    elements: Vec<Element>,
+}
+
+impl Proof {
+    /// Create an empty proof.
+    pub fn new() -> Proof {
+        Default::default()
+    }
+
+    /// Create an empty proof which starts with a custom phrase instead of
+    /// "Proof".
+    pub fn with_phrase(phrase: &str) -> Proof {
+        Proof {
+            phrase: Some(phrase.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Add an element to the proof.
+    pub fn push<I>(&mut self, element: I) -> &mut Self
+    where
+        I: Into<Element>,
+    {
+        self.elements.push(element.into());
+        self
+    }
+
+    /// Get the custom leading phrase, if there is one.
+    pub fn get_phrase(&self) -> Option<&str> {
+        self.phrase.as_deref()
+    }
+
+    /// Iterate over the elements in this proof.
+    pub fn iter(&self) -> Iter<'_, Element> {
+        self.elements.iter()
+    }
+
+    /// Mutably iterate over the elements in this proof.
+    pub fn iter_mut(&mut self) -> IterMut<'_, Element> {
+        self.elements.iter_mut()
+    }
+}
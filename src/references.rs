@@ -0,0 +1,202 @@
+//! Helpers for referring to labelled parts of a document.
+//!
+//! Each helper returns the raw command as a `String` so it can be embedded in
+//! a paragraph's text. Any packages the commands need will be imported
+//! automatically when the document is rendered.
+
+use std::fmt::{self, Display, Formatter};
+
+use escape::escape;
+
+/// Reference a label using `cleveref`, which inserts the kind of thing being
+/// referred to (e.g. "fig. 1").
+///
+/// ```rust
+/// assert_eq!(latex::cref("fig:plot"), r"\cref{fig:plot}");
+/// ```
+pub fn cref(label: &str) -> String {
+    format!(r"\cref{{{}}}", label)
+}
+
+/// Like `cref()`, but capitalised for use at the start of a sentence (e.g.
+/// "Figure 1").
+///
+/// ```rust
+/// assert_eq!(latex::cref_upper("fig:plot"), r"\Cref{fig:plot}");
+/// ```
+pub fn cref_upper(label: &str) -> String {
+    format!(r"\Cref{{{}}}", label)
+}
+
+/// Reference a label using `hyperref`'s `\autoref`, which (like `cref()`)
+/// inserts the kind of thing being referred to, without needing `cleveref`.
+///
+/// ```rust
+/// assert_eq!(latex::auto_reference("sec:intro"), r"\autoref{sec:intro}");
+/// ```
+pub fn auto_reference(label: &str) -> String {
+    format!(r"\autoref{{{}}}", label)
+}
+
+/// Reference a label using `hyperref`'s `\nameref`, which prints the title
+/// of the section (or other named thing) being referred to.
+///
+/// ```rust
+/// assert_eq!(latex::name_reference("sec:intro"), r"\nameref{sec:intro}");
+/// ```
+pub fn name_reference(label: &str) -> String {
+    format!(r"\nameref{{{}}}", label)
+}
+
+/// Turn some human-readable text (e.g. a section's name) into a label which
+/// is safe to use as a hyperlink target.
+///
+/// Runs of whitespace become a single `-`, and anything other than ASCII
+/// letters, digits, and `:`, `-`, `_` or `.` is dropped. The text itself
+/// is left alone wherever it's displayed.
+///
+/// Labels the crate makes up itself (e.g. with `Section::with_auto_label()`)
+/// are already sanitized, but labels you set by hand are used exactly as
+/// given, so that references to them still match. Pass them through this
+/// first if they might contain spaces or other special characters.
+///
+/// ```rust
+/// assert_eq!(latex::sanitize_label("Results & Discussion"), "Results-Discussion");
+/// ```
+pub fn sanitize_label(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|&c| c.is_ascii_alphanumeric() || ":-_.".contains(c))
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Link to an email address, using `hyperref`'s `\href`. The link text is
+/// escaped, but the address is passed through as it is.
+///
+/// ```rust
+/// assert_eq!(
+///     latex::mailto("jane@example.com", "Jane Doe"),
+///     r"\href{mailto:jane@example.com}{Jane Doe}"
+/// );
+/// ```
+pub fn mailto(address: &str, text: &str) -> String {
+    format!(r"\href{{mailto:{}}}{{{}}}", address, escape(text))
+}
+
+/// Link to a DOI through `https://doi.org`, showing the DOI itself as the
+/// link text. A `doi:` or `https://doi.org/` prefix on `doi` is ignored, and
+/// only the link text is escaped.
+///
+/// ```rust
+/// assert_eq!(
+///     latex::doi_link("10.1000/182"),
+///     r"\href{https://doi.org/10.1000/182}{10.1000/182}"
+/// );
+/// ```
+pub fn doi_link(doi: &str) -> String {
+    let doi = doi
+        .trim_start_matches("https://doi.org/")
+        .trim_start_matches("doi:");
+    format!(r"\href{{https://doi.org/{}}}{{{}}}", doi, escape(doi))
+}
+
+/// Options for `hyperref`, passed to `\hypersetup` by
+/// `Preamble::hypersetup()`.
+///
+/// ```rust
+/// use latex::HyperSetup;
+///
+/// let options = HyperSetup {
+///     bookmarks_depth: Some(2),
+///     colorlinks: Some(true),
+///     ..Default::default()
+/// };
+/// assert_eq!(options.to_string(), "bookmarksdepth=2,colorlinks=true");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HyperSetup {
+    /// How many levels of sectioning get a PDF bookmark.
+    pub bookmarks_depth: Option<u32>,
+    /// Colour the text of links instead of drawing boxes around them.
+    pub colorlinks: Option<bool>,
+    /// The title stored in the PDF's metadata.
+    pub pdf_title: Option<String>,
+}
+
+impl Display for HyperSetup {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut set = Vec::new();
+        if let Some(depth) = self.bookmarks_depth {
+            set.push(format!("bookmarksdepth={}", depth));
+        }
+        if let Some(colorlinks) = self.colorlinks {
+            set.push(format!("colorlinks={}", colorlinks));
+        }
+        if let Some(ref title) = self.pdf_title {
+            set.push(format!("pdftitle={{{}}}", title));
+        }
+
+        write!(f, "{}", set.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lower_and_upper_case_cleveref() {
+        assert_eq!(cref("sec:intro"), "\\cref{sec:intro}");
+        assert_eq!(cref_upper("sec:intro"), "\\Cref{sec:intro}");
+    }
+
+    #[test]
+    fn autoref_reference() {
+        assert_eq!(auto_reference("eq:energy"), "\\autoref{eq:energy}");
+    }
+
+    #[test]
+    fn nameref_reference() {
+        assert_eq!(name_reference("sec:method"), "\\nameref{sec:method}");
+    }
+
+    #[test]
+    fn clean_labels_from_section_names() {
+        let section = ::Section::new("My Section!");
+        assert_eq!(sanitize_label(&section.name), "My-Section");
+        assert_eq!(section.name, "My Section!");
+
+        assert_eq!(sanitize_label("  sec:a  #b "), "sec:a-b");
+    }
+
+    #[test]
+    fn email_links() {
+        assert_eq!(
+            mailto("a@b.org", "Contact us"),
+            "\\href{mailto:a@b.org}{Contact us}"
+        );
+        assert_eq!(
+            mailto("r_d@b.org", "R&D"),
+            "\\href{mailto:r_d@b.org}{R\\&D}"
+        );
+    }
+
+    #[test]
+    fn doi_links_ignore_existing_prefixes() {
+        let should_be = "\\href{https://doi.org/10.1145/359576.359579}{10.1145/359576.359579}";
+
+        assert_eq!(doi_link("10.1145/359576.359579"), should_be);
+        assert_eq!(doi_link("doi:10.1145/359576.359579"), should_be);
+        assert_eq!(doi_link("https://doi.org/10.1145/359576.359579"), should_be);
+
+        assert_eq!(
+            doi_link("10.1002/(SICI)1097_4571"),
+            r"\href{https://doi.org/10.1002/(SICI)1097_4571}{10.1002/(SICI)1097\_4571}"
+        );
+    }
+}
@@ -1,16 +1,55 @@
-use std::slice::Iter;
+use std::slice::{Iter, IterMut};
 
 use document::Element;
+use references::sanitize_label;
+
+/// The plain ASCII versions of accented (lowercase) Latin letters, used when
+/// making up labels.
+const TRANSLITERATIONS: &[(&str, &str)] = &[
+    ("àáâãäåā", "a"),
+    ("æ", "ae"),
+    ("çćč", "c"),
+    ("ď", "d"),
+    ("èéêëēęě", "e"),
+    ("ìíîïī", "i"),
+    ("ł", "l"),
+    ("ñńň", "n"),
+    ("òóôõöøō", "o"),
+    ("œ", "oe"),
+    ("ř", "r"),
+    ("śšş", "s"),
+    ("ß", "ss"),
+    ("ťţ", "t"),
+    ("ùúûüūů", "u"),
+    ("ýÿ", "y"),
+    ("źżž", "z"),
+];
 
 /// A document Section.
 ///
 /// Like the `Document` type, a `Section` is more or less just a collection of
 /// `Element`s. When rendered it will start with `\section{Section Name}` and
 /// then each element will be rendered in turn.
+///
+/// Sections can be nested inside each other, in which case the sectioning
+/// command is picked based on how deeply the section is nested (`\section`,
+/// `\subsection`, `\subsubsection`, and so on).
+///
+/// ```rust
+/// use latex::Section;
+///
+/// let mut background = Section::new("Background");
+/// background.push("Some history.");
+///
+/// let mut intro = Section::new("Introduction");
+/// intro.push("Opening remarks.").push_section(background);
+/// ```
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Section {
     /// The name of the section.
     pub name: String,
+    label: Option<String>,
+    packages: Vec<(String, Option<String>)>,
     elements: Vec<Element>,
 }
 
@@ -23,6 +62,90 @@ impl Section {
         }
     }
 
+    /// Give the section a label so it can be referenced later.
+    ///
+    /// The label is used as it is, so use `sanitize_label()` on it first if
+    /// it might contain characters which break hyperlinks (e.g. spaces).
+    pub fn label(&mut self, name: &str) -> &mut Self {
+        self.label = Some(name.to_string());
+        self
+    }
+
+    /// Label the section with `sec:` followed by a slug of its name, which
+    /// can then be read back with `get_label()`.
+    ///
+    /// Accented Latin letters are replaced by their plain versions (e.g.
+    /// "Über" becomes `uber`). If nothing is left of the name, the slug is
+    /// made from the hex codes of its characters instead, or is `untitled`
+    /// for an empty name.
+    ///
+    /// ```rust
+    /// use latex::Section;
+    ///
+    /// let mut section = Section::new("Results & Discussion");
+    /// section.with_auto_label();
+    /// assert_eq!(section.get_label(), Some("sec:results-discussion"));
+    /// ```
+    pub fn with_auto_label(&mut self) -> &mut Self {
+        let name: String = self
+            .name
+            .to_lowercase()
+            .chars()
+            .map(
+                |c| match TRANSLITERATIONS.iter().find(|&&(from, _)| from.contains(c)) {
+                    Some(&(_, to)) => to.to_string(),
+                    None => c.to_string(),
+                },
+            )
+            .collect();
+
+        let mut slug = sanitize_label(&name);
+        if slug.is_empty() && self.name.trim().is_empty() {
+            slug = "untitled".to_string();
+        } else if slug.is_empty() {
+            slug = self
+                .name
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .map(|c| format!("u{:04x}", c as u32))
+                .collect::<Vec<_>>()
+                .join("-");
+        }
+
+        self.label(&format!("sec:{}", slug))
+    }
+
+    /// Get the section's label, if there is one.
+    pub fn get_label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Record that the section's contents need a package, so it's imported
+    /// into any document the section is rendered in (unless the document
+    /// already imports it).
+    ///
+    /// ```rust
+    /// use latex::{Element, Section};
+    ///
+    /// let mut diagram = Section::new("Architecture");
+    /// diagram
+    ///     .require_package("tikz", None)
+    ///     .push(Element::UserDefined(r"\tikz \draw (0,0) -- (1,1);".to_string()));
+    /// ```
+    pub fn require_package(&mut self, name: &str, options: Option<&str>) -> &mut Self {
+        self.packages
+            .push((name.to_string(), options.map(ToString::to_string)));
+        self
+    }
+
+    /// Iterate over the packages this section requires, and any options
+    /// they should be loaded with.
+    pub fn required_packages(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.packages
+            .iter()
+            .map(|(name, options)| (name.as_str(), options.as_deref()))
+    }
+
     /// Add an element to the Section.
     pub fn push<I>(&mut self, element: I) -> &mut Self
     where
@@ -32,11 +155,22 @@ impl Section {
         self
     }
 
+    /// Add a child section, which will be rendered one level deeper than this
+    /// one (e.g. as a `\subsection`).
+    pub fn push_section(&mut self, child: Section) -> &mut Self {
+        self.push(child)
+    }
+
     /// Iterate over the elements in this list.
-    pub fn iter(&self) -> Iter<Element> {
+    pub fn iter(&self) -> Iter<'_, Element> {
         self.elements.iter()
     }
 
+    /// Mutably iterate over the elements in this section.
+    pub fn iter_mut(&mut self) -> IterMut<'_, Element> {
+        self.elements.iter_mut()
+    }
+
     /// Is this section empty?
     pub fn is_empty(&self) -> bool {
         self.elements.is_empty()
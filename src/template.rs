@@ -0,0 +1,25 @@
+use document::{Element, Preamble};
+
+/// A reusable template, such as an institution's house style, which can be
+/// applied to a document with `Document::apply_template()`.
+///
+/// ```rust
+/// use latex::{Document, Element, Template};
+///
+/// let mut template = Template::default();
+/// template.preamble.use_package("geometry");
+/// template.header.push(Element::TitlePage);
+///
+/// let mut doc = Document::default();
+/// doc.push("Generated content.");
+/// doc.apply_template(&template);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Template {
+    /// Preamble items to add to the document.
+    pub preamble: Preamble,
+    /// Elements to put before the document's own elements.
+    pub header: Vec<Element>,
+    /// Elements to put after the document's own elements.
+    pub footer: Vec<Element>,
+}
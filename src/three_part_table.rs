@@ -0,0 +1,62 @@
+use document::Element;
+
+/// A table with footnote-style notes underneath it, using the
+/// `threeparttable` package.
+///
+/// The `tabular` is written as raw LaTeX, and each note becomes an `\item`
+/// in the `tablenotes` block. Notes can be marked in the table with
+/// `\tnote{a}` and given the matching `[a]` at the start of the note.
+///
+/// # Examples
+///
+/// ```rust
+/// use latex::{Document, ThreePartTable};
+///
+/// let table = ThreePartTable {
+///     tabular: "\\begin{tabular}{ll}\nA & B\\tnote{a}\n\\end{tabular}".to_string(),
+///     notes: vec!["[a] Estimated.".to_string()],
+/// };
+/// let mut doc = Document::default();
+/// doc.push(table);
+/// ```
+///
+/// Which would be rendered as:
+///
+/// ```tex
+/// \begin{threeparttable}
+/// \begin{tabular}{ll}
+/// A & B\tnote{a}
+/// \end{tabular}
+/// \begin{tablenotes}
+/// \item[a] Estimated.
+/// \end{tablenotes}
+/// \end{threeparttable}
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ThreePartTable {
+    /// The table itself, as a complete `tabular` environment.
+    pub tabular: String,
+    /// The notes printed under the table.
+    pub notes: Vec<String>,
+}
+
+impl From<ThreePartTable> for Element {
+    fn from(other: ThreePartTable) -> Self {
+        let mut lines: Vec<String> = other.tabular.lines().map(ToString::to_string).collect();
+
+        if !other.notes.is_empty() {
+            lines.push(r"\begin{tablenotes}".to_string());
+            for note in other.notes {
+                // notes with a marker are written straight after \item
+                if note.starts_with('[') {
+                    lines.push(format!(r"\item{}", note));
+                } else {
+                    lines.push(format!(r"\item {}", note));
+                }
+            }
+            lines.push(r"\end{tablenotes}".to_string());
+        }
+
+        Element::Environment("threeparttable".to_string(), lines)
+    }
+}
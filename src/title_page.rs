@@ -0,0 +1,217 @@
+use document::Element;
+
+/// A builder for a custom `titlepage`, such as the one at the front of a
+/// thesis.
+///
+/// Each field which has been set is centred on its own line, with the title
+/// first, and the fields are separated by a configurable amount of vertical
+/// space.
+///
+/// ```rust
+/// use latex::{Document, TitlePageBuilder};
+///
+/// let mut doc = Document::default();
+/// doc.push(
+///     TitlePageBuilder::new("On the Nature of Things")
+///         .author("A. Student")
+///         .university("University of Somewhere")
+///         .supervisor("Prof. B. Advisor")
+///         .build(),
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TitlePageBuilder {
+    title: String,
+    author: Option<String>,
+    university: Option<String>,
+    degree: Option<String>,
+    supervisor: Option<String>,
+    date: Option<String>,
+    spacing: String,
+}
+
+impl TitlePageBuilder {
+    /// Start building a title page with the provided title.
+    pub fn new(title: &str) -> TitlePageBuilder {
+        TitlePageBuilder {
+            title: title.to_string(),
+            author: None,
+            university: None,
+            degree: None,
+            supervisor: None,
+            date: None,
+            spacing: "1.5cm".to_string(),
+        }
+    }
+
+    /// Set the author's name.
+    pub fn author(&mut self, name: &str) -> &mut Self {
+        self.author = Some(name.to_string());
+        self
+    }
+
+    /// Set the university (or other institution).
+    pub fn university(&mut self, name: &str) -> &mut Self {
+        self.university = Some(name.to_string());
+        self
+    }
+
+    /// Set the degree the document is submitted for.
+    pub fn degree(&mut self, degree: &str) -> &mut Self {
+        self.degree = Some(degree.to_string());
+        self
+    }
+
+    /// Set the supervisor's name.
+    pub fn supervisor(&mut self, name: &str) -> &mut Self {
+        self.supervisor = Some(name.to_string());
+        self
+    }
+
+    /// Set the date. Use `\today` for the date the document is compiled.
+    pub fn date(&mut self, date: &str) -> &mut Self {
+        self.date = Some(date.to_string());
+        self
+    }
+
+    /// Set the vertical space between each field (`1.5cm` by default).
+    pub fn spacing(&mut self, length: &str) -> &mut Self {
+        self.spacing = length.to_string();
+        self
+    }
+
+    /// Build the `titlepage` environment.
+    pub fn build(&self) -> Element {
+        let mut fields = vec![format!(r"{{\Huge\bfseries {}\par}}", self.title)];
+
+        if let Some(ref author) = self.author {
+            fields.push(format!(r"{{\Large {}\par}}", author));
+        }
+        if let Some(ref degree) = self.degree {
+            fields.push(format!(r"{{\large {}\par}}", degree));
+        }
+        if let Some(ref university) = self.university {
+            fields.push(format!(r"{{\large {}\par}}", university));
+        }
+        if let Some(ref supervisor) = self.supervisor {
+            fields.push(format!(r"{{\large Supervisor: {}\par}}", supervisor));
+        }
+        if let Some(ref date) = self.date {
+            fields.push(format!(r"{{\large {}\par}}", date));
+        }
+
+        let mut lines = vec![r"\centering".to_string()];
+        for (i, field) in fields.into_iter().enumerate() {
+            if i > 0 {
+                lines.push(format!(r"\vspace{{{}}}", self.spacing));
+            }
+            lines.push(field);
+        }
+
+        Element::Environment("titlepage".to_string(), lines)
+    }
+}
+
+/// A journal-style list of keywords, usually placed just after the abstract.
+///
+/// Classes disagree on how keywords are written, so they're rendered as a
+/// `\keywords{...}` command by default, but a different command or an
+/// environment can be used instead.
+///
+/// ```rust
+/// use latex::{Document, Keywords};
+///
+/// let mut doc = Document::default();
+/// doc.push(Keywords::new(&["graphs", "colouring"]).environment("IEEEkeywords").build());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keywords {
+    words: Vec<String>,
+    name: String,
+    environment: bool,
+}
+
+impl Keywords {
+    /// Start building a keyword list.
+    pub fn new(words: &[&str]) -> Keywords {
+        Keywords {
+            words: words.iter().map(ToString::to_string).collect(),
+            name: "keywords".to_string(),
+            environment: false,
+        }
+    }
+
+    /// Use a different command for the keywords (without the backslash).
+    pub fn command(&mut self, name: &str) -> &mut Self {
+        self.name = name.to_string();
+        self.environment = false;
+        self
+    }
+
+    /// Put the keywords inside an environment instead of passing them to a
+    /// command.
+    pub fn environment(&mut self, name: &str) -> &mut Self {
+        self.name = name.to_string();
+        self.environment = true;
+        self
+    }
+
+    /// Build the keywords command or environment.
+    pub fn build(&self) -> Element {
+        let words = self.words.join(", ");
+
+        if self.environment {
+            Element::Environment(self.name.clone(), vec![words])
+        } else {
+            Element::UserDefined(format!(r"\{}{{{}}}", self.name, words))
+        }
+    }
+}
+
+/// A document's abstract.
+///
+/// Some templates want the abstract single-spaced even when the rest of the
+/// document isn't, which `single_spaced()` does with the `setspace` package.
+///
+/// ```rust
+/// use latex::{Abstract, Document};
+///
+/// let mut doc = Document::default();
+/// doc.push(Abstract::new("We colour some graphs.").single_spaced(true).build());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Abstract {
+    text: String,
+    single_spaced: bool,
+}
+
+impl Abstract {
+    /// Start building an abstract.
+    pub fn new(text: &str) -> Abstract {
+        Abstract {
+            text: text.to_string(),
+            single_spaced: false,
+        }
+    }
+
+    /// Set whether the abstract is wrapped in a `singlespace` environment.
+    pub fn single_spaced(&mut self, single_spaced: bool) -> &mut Self {
+        self.single_spaced = single_spaced;
+        self
+    }
+
+    /// Build the `abstract` environment.
+    pub fn build(&self) -> Element {
+        let lines = if self.single_spaced {
+            vec![
+                r"\begin{singlespace}".to_string(),
+                self.text.clone(),
+                r"\end{singlespace}".to_string(),
+            ]
+        } else {
+            vec![self.text.clone()]
+        };
+
+        Element::Environment("abstract".to_string(), lines)
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt::Write;
+
+use super::Visitor;
+use document::{Document, Element};
+use failure::Error;
+use layout::{AdjustBox, Aligned, ColorBox, Landscape, WrapFigure};
+use proof::Proof;
+use section::Section;
+
+/// Render an indented outline of a document's structure, showing the type of
+/// each element rather than its LaTeX.
+pub(crate) fn debug_tree(doc: &Document) -> String {
+    let mut tree = DebugTree::default();
+    writeln!(tree.buffer, "Document({})", doc.class).unwrap();
+    tree.depth = 1;
+
+    for element in doc.iter() {
+        tree.visit_element(element)
+            .expect("writing to a String never fails");
+    }
+
+    tree.buffer
+}
+
+/// The `Visitor` which builds up the outline for `debug_tree()`.
+#[derive(Debug, Default)]
+struct DebugTree {
+    buffer: String,
+    depth: usize,
+}
+
+impl DebugTree {
+    /// Add a line to the outline at the current depth.
+    fn line(&mut self, text: &str) -> Result<(), Error> {
+        writeln!(
+            self.buffer,
+            "{:indent$}{}",
+            "",
+            text,
+            indent = self.depth * 2
+        )?;
+        Ok(())
+    }
+
+    /// Add a line for a container, then its children one level deeper.
+    fn nested<'a, I>(&mut self, text: &str, children: I) -> Result<(), Error>
+    where
+        I: Iterator<Item = &'a Element>,
+    {
+        self.line(text)?;
+        self.depth += 1;
+        for element in children {
+            self.visit_element(element)?;
+        }
+        self.depth -= 1;
+
+        Ok(())
+    }
+}
+
+impl Visitor for DebugTree {
+    fn visit_element(&mut self, element: &Element) -> Result<(), Error> {
+        match *element {
+            Element::Para(_) => self.line("Paragraph"),
+            Element::Part { ref title, .. } => self.line(&format!("Part({:?})", title)),
+            Element::Chapter { ref title, .. } => self.line(&format!("Chapter({:?})", title)),
+            Element::Section(ref section) => self.visit_section(section),
+            Element::TableOfContents => self.line("TableOfContents"),
+            Element::TitlePage => self.line("TitlePage"),
+            Element::ClearPage => self.line("ClearPage"),
+            Element::ParagraphBreak => self.line("ParagraphBreak"),
+            Element::NewGeometry(ref options) => self.line(&format!("NewGeometry({:?})", options)),
+            Element::RestoreGeometry => self.line("RestoreGeometry"),
+            Element::ThisPageStyle(ref style) => self.line(&format!("ThisPageStyle({})", style)),
+            Element::PageNumbering(style) => self.line(&format!("PageNumbering({})", style)),
+            Element::SetCounter { ref name, .. } => self.line(&format!("SetCounter({:?})", name)),
+            Element::AddToCounter { ref name, .. } => {
+                self.line(&format!("AddToCounter({:?})", name))
+            }
+            Element::Lipsum(_) => self.line("Lipsum"),
+            Element::PrintEndnotes => self.line("PrintEndnotes"),
+            Element::PrintShorthands => self.line("PrintShorthands"),
+            Element::PrintNomenclature => self.line("PrintNomenclature"),
+            Element::IncludePdf { ref path, .. } => self.line(&format!("IncludePdf({:?})", path)),
+            Element::Align(_) => self.line("Align"),
+            Element::LongEquation(ref equation) => {
+                self.line(&format!("LongEquation({:?})", equation.kind))
+            }
+            Element::TikzPicture(_) => self.line("TikzPicture"),
+            Element::Plot(_) => self.line("Plot"),
+            Element::CsvTable(ref table) => self.line(&format!("CsvTable({:?})", table.path())),
+            Element::Proof(ref proof) => self.visit_proof(proof),
+            Element::Landscape(ref landscape) => self.visit_landscape(landscape),
+            Element::AdjustBox(ref adjust_box) => self.visit_adjust_box(adjust_box),
+            Element::WrapFigure(ref figure) => self.visit_wrap_figure(figure),
+            Element::ColorBox(ref color_box) => self.visit_color_box(color_box),
+            Element::Aligned(ref aligned) => self.visit_aligned(aligned),
+            Element::ManualBibliography(_) => self.line("ManualBibliography"),
+            Element::Acronyms(_) => self.line("Acronyms"),
+            Element::Comment {
+                ref name,
+                ref content,
+                ..
+            } => self.nested(&format!("Comment({:?})", name), content.iter()),
+            Element::Environment(ref name, _) => self.line(&format!("Environment({:?})", name)),
+            Element::UserDefined(_) => self.line("UserDefined"),
+            Element::List(ref list) => self.line(&format!("List({:?})", list.kind)),
+            Element::Input(ref s) => self.line(&format!("Input({:?})", s)),
+            Element::Subfile(ref s) => self.line(&format!("Subfile({:?})", s)),
+            Element::Import { ref dir, ref file } => {
+                self.line(&format!("Import({:?}, {:?})", dir, file))
+            }
+            Element::InlineFile(ref path) => self.line(&format!("InlineFile({:?})", path)),
+            Element::_Other => unreachable!(),
+        }
+    }
+
+    fn visit_section(&mut self, section: &Section) -> Result<(), Error> {
+        self.nested(&format!("Section({:?})", section.name), section.iter())
+    }
+
+    fn visit_proof(&mut self, proof: &Proof) -> Result<(), Error> {
+        self.nested("Proof", proof.iter())
+    }
+
+    fn visit_landscape(&mut self, landscape: &Landscape) -> Result<(), Error> {
+        self.nested("Landscape", landscape.iter())
+    }
+
+    fn visit_adjust_box(&mut self, adjust_box: &AdjustBox) -> Result<(), Error> {
+        self.nested("AdjustBox", adjust_box.iter())
+    }
+
+    fn visit_wrap_figure(&mut self, figure: &WrapFigure) -> Result<(), Error> {
+        self.nested("WrapFigure", figure.iter())
+    }
+
+    fn visit_color_box(&mut self, color_box: &ColorBox) -> Result<(), Error> {
+        self.nested("ColorBox", color_box.iter())
+    }
+
+    fn visit_aligned(&mut self, aligned: &Aligned) -> Result<(), Error> {
+        self.nested(&format!("Aligned({:?})", aligned.alignment), aligned.iter())
+    }
+}
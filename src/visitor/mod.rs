@@ -1,16 +1,37 @@
 //! A trait which lets you walk your document's AST.
 
+mod debug_tree;
+mod numbering;
 mod printer;
+mod requirements;
+mod summary;
+mod validator;
 
-pub use self::printer::{print, Printer};
+pub(crate) use self::debug_tree::debug_tree;
+pub(crate) use self::numbering::resolve_numbers;
+pub(crate) use self::printer::{body_string, preamble_string, required_packages};
+pub use self::printer::{
+    normalize_latex, preview, print, print_with, write_document, LineEnding, Printer, RenderError,
+    RenderOptions,
+};
+pub(crate) use self::summary::element_summary;
+pub use self::summary::ElementSummary;
+pub use self::validator::{validate, Diagnostic};
 
+use acronyms::AcronymList;
+use bibliography::ManualBibliography;
+use csv_table::CsvTable;
 use document::{Document, DocumentClass, Element, Preamble};
-use equations::{Align, Equation};
+use equations::{Align, Equation, LongEquation};
 use failure::Error;
+use layout::{AdjustBox, Aligned, ColorBox, Landscape, WrapFigure};
 use lists::{Item, List};
 use paragraph::{Paragraph, ParagraphElement};
+use plot::Plot;
+use proof::Proof;
 use section::Section;
-use std::ops::Deref;
+use std::ops::{Deref, RangeInclusive};
+use std::path::Path;
 
 /// A trait which uses the [Visitor Pattern] to recursively visit each node in
 /// a `Document`.
@@ -36,20 +57,48 @@ pub trait Visitor {
     /// `visit_*()` methods.
     ///
     /// > **Note:** You probably don't want to implement this one yourself. If
-    /// you forget to recursively visit each and every variant of `Element`
-    /// you may end up accidentally ignoring half your document!
+    /// > you forget to recursively visit each and every variant of `Element`
+    /// > you may end up accidentally ignoring half your document!
     fn visit_element(&mut self, elem: &Element) -> Result<(), Error> {
         match *elem {
             Element::Para(ref p) => self.visit_paragraph(p)?,
             Element::Section(ref s) => self.visit_section(s)?,
             Element::UserDefined(ref s) => self.visit_user_defined_line(s)?,
             Element::Align(ref equations) => self.visit_align(equations)?,
+            Element::LongEquation(ref equation) => self.visit_long_equation(equation)?,
+            Element::TikzPicture(ref body) => self.visit_tikz_picture(body)?,
+            Element::Plot(ref plot) => self.visit_plot(plot)?,
+            Element::CsvTable(ref table) => self.visit_csv_table(table)?,
+            Element::Proof(ref proof) => self.visit_proof(proof)?,
+            Element::Landscape(ref landscape) => self.visit_landscape(landscape)?,
+            Element::AdjustBox(ref adjust_box) => self.visit_adjust_box(adjust_box)?,
+            Element::WrapFigure(ref figure) => self.visit_wrap_figure(figure)?,
+            Element::ColorBox(ref color_box) => self.visit_color_box(color_box)?,
+            Element::Aligned(ref aligned) => self.visit_aligned(aligned)?,
+            Element::Lipsum(ref paragraphs) => self.visit_lipsum(paragraphs.as_ref())?,
+            Element::IncludePdf {
+                ref path,
+                ref pages,
+            } => self.visit_include_pdf(path, pages.as_ref())?,
+            Element::ManualBibliography(ref bib) => self.visit_manual_bibliography(bib)?,
+            Element::Acronyms(ref acronyms) => self.visit_acronyms(acronyms)?,
+            Element::PrintNomenclature => self.visit_print_nomenclature()?,
+            Element::NewGeometry(ref options) => self.visit_new_geometry(options)?,
+            Element::RestoreGeometry => self.visit_restore_geometry()?,
+            Element::Comment {
+                ref name,
+                included,
+                ref content,
+            } => self.visit_comment(name, included, content)?,
 
             Element::Environment(ref name, ref lines) => {
                 self.visit_custom_environment(name, lines.iter().map(Deref::deref))?
             }
             Element::List(ref list) => self.visit_list(list)?,
             Element::Input(ref s) => self.visit_input(s)?,
+            Element::Subfile(ref s) => self.visit_subfile(s)?,
+            Element::Import { ref dir, ref file } => self.visit_import(dir, file)?,
+            Element::InlineFile(ref path) => self.visit_inline_file(path)?,
 
             _ => {}
         }
@@ -77,6 +126,21 @@ pub trait Visitor {
         Ok(())
     }
 
+    /// Visit the inclusion of a subfile.
+    fn visit_subfile(&mut self, path: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Visit the `\import` of a file in another directory.
+    fn visit_import(&mut self, dir: &str, file: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Visit an external file which will be copied into the document.
+    fn visit_inline_file(&mut self, path: &Path) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Visit a paragraph, and every `ParagraphElement` in it.
     fn visit_paragraph(&mut self, paragraph: &Paragraph) -> Result<(), Error> {
         for elem in &paragraph.elements {
@@ -105,6 +169,138 @@ pub trait Visitor {
         Ok(())
     }
 
+    /// Visit an equation which is broken over several lines.
+    fn visit_long_equation(&mut self, equation: &LongEquation) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Visit the body of a TikZ picture.
+    fn visit_tikz_picture(&mut self, body: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Visit a `Plot` of some data.
+    fn visit_plot(&mut self, plot: &Plot) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Visit a `CsvTable`.
+    fn visit_csv_table(&mut self, table: &CsvTable) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Visit a `Proof` and then recursively visit each of its `Element`s.
+    fn visit_proof(&mut self, proof: &Proof) -> Result<(), Error> {
+        for elem in proof.iter() {
+            self.visit_element(elem)?;
+        }
+
+        Ok(())
+    }
+
+    /// Visit a `Landscape` section and then recursively visit each of its
+    /// `Element`s.
+    fn visit_landscape(&mut self, landscape: &Landscape) -> Result<(), Error> {
+        for elem in landscape.iter() {
+            self.visit_element(elem)?;
+        }
+
+        Ok(())
+    }
+
+    /// Visit an `AdjustBox` and then recursively visit each of its
+    /// `Element`s.
+    fn visit_adjust_box(&mut self, adjust_box: &AdjustBox) -> Result<(), Error> {
+        for elem in adjust_box.iter() {
+            self.visit_element(elem)?;
+        }
+
+        Ok(())
+    }
+
+    /// Visit a `WrapFigure` and then recursively visit each of its
+    /// `Element`s.
+    fn visit_wrap_figure(&mut self, figure: &WrapFigure) -> Result<(), Error> {
+        for elem in figure.iter() {
+            self.visit_element(elem)?;
+        }
+
+        Ok(())
+    }
+
+    /// Visit a `ColorBox` and then recursively visit each of its `Element`s.
+    fn visit_color_box(&mut self, color_box: &ColorBox) -> Result<(), Error> {
+        for elem in color_box.iter() {
+            self.visit_element(elem)?;
+        }
+
+        Ok(())
+    }
+
+    /// Visit an `Aligned` block and then recursively visit each of its
+    /// `Element`s.
+    fn visit_aligned(&mut self, aligned: &Aligned) -> Result<(), Error> {
+        for elem in aligned.iter() {
+            self.visit_element(elem)?;
+        }
+
+        Ok(())
+    }
+
+    /// Visit a block from the `comment` package and then recursively visit
+    /// each of its `Element`s.
+    fn visit_comment(
+        &mut self,
+        name: &str,
+        included: bool,
+        content: &[Element],
+    ) -> Result<(), Error> {
+        for elem in content {
+            self.visit_element(elem)?;
+        }
+
+        Ok(())
+    }
+
+    /// Visit some `lipsum` placeholder text.
+    fn visit_lipsum(&mut self, paragraphs: Option<&RangeInclusive<usize>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Visit some pages included from an external PDF.
+    fn visit_include_pdf(
+        &mut self,
+        path: &Path,
+        pages: Option<&RangeInclusive<usize>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Visit a hand-written `ManualBibliography`.
+    fn visit_manual_bibliography(&mut self, bib: &ManualBibliography) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Visit the document's `AcronymList`.
+    fn visit_acronyms(&mut self, acronyms: &AcronymList) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Visit the place where the nomenclature is printed.
+    fn visit_print_nomenclature(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Visit a change to the page margins.
+    fn visit_new_geometry(&mut self, options: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Visit the place where the usual page margins are restored.
+    fn visit_restore_geometry(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Visit a single `Equation`.
     fn visit_equation(&mut self, equation: &Equation) -> Result<(), Error> {
         Ok(())
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use super::Visitor;
+use document::{Document, DocumentClass, Element};
+use equations::{Equation, LongEquation};
+use failure::Error;
+use section::Section;
+
+/// Work out the number each label in a document will resolve to.
+pub(crate) fn resolve_numbers(doc: &Document) -> HashMap<String, String> {
+    let has_chapters = doc.class == DocumentClass::Book || doc.class == DocumentClass::Report;
+    let mut numbering = Numbering {
+        has_chapters,
+        // LaTeX's default `secnumdepth` numbers down to `\subsubsection`,
+        // or `\subsection` for classes with chapters
+        numbered_depth: if has_chapters { 2 } else { 3 },
+        ..Default::default()
+    };
+
+    for element in doc.iter() {
+        numbering
+            .visit_element(element)
+            .expect("numbering a document never fails");
+    }
+
+    numbering.labels
+}
+
+/// The `Visitor` which simulates LaTeX's counters for `resolve_numbers()`.
+#[derive(Debug, Default)]
+struct Numbering {
+    has_chapters: bool,
+    numbered_depth: usize,
+    chapter: usize,
+    sections: Vec<usize>,
+    depth: usize,
+    equation: usize,
+    figure: usize,
+    table: usize,
+    labels: HashMap<String, String>,
+}
+
+impl Numbering {
+    /// Prefix a number with the chapter, for classes which number things
+    /// within chapters.
+    fn within_chapter(&self, number: String) -> String {
+        if self.has_chapters {
+            format!("{}.{}", self.chapter, number)
+        } else {
+            number
+        }
+    }
+
+    /// Number the labels in a raw `figure` or `table` environment.
+    fn visit_float(&mut self, name: &str, lines: &[String]) {
+        let counter = match name.trim_end_matches('*') {
+            "figure" => &mut self.figure,
+            "table" => &mut self.table,
+            _ => return,
+        };
+        *counter += 1;
+
+        let number = counter.to_string();
+        let number = self.within_chapter(number);
+        for label in lines.iter().flat_map(|line| labels_in(line)) {
+            self.labels.insert(label, number.clone());
+        }
+    }
+}
+
+/// Find the names of any `\label{...}`s in a line of raw LaTeX.
+fn labels_in(line: &str) -> Vec<String> {
+    line.split(r"\label{")
+        .skip(1)
+        .filter_map(|rest| rest.find('}').map(|end| rest[..end].to_string()))
+        .collect()
+}
+
+impl Visitor for Numbering {
+    fn visit_element(&mut self, element: &Element) -> Result<(), Error> {
+        match *element {
+            Element::Chapter { starred: false, .. } => {
+                self.chapter += 1;
+                self.sections.clear();
+                self.equation = 0;
+                self.figure = 0;
+                self.table = 0;
+            }
+            Element::Section(ref section) => self.visit_section(section)?,
+            Element::Align(ref align) => self.visit_align(align)?,
+            Element::LongEquation(ref equation) => self.visit_long_equation(equation)?,
+            Element::Proof(ref proof) => self.visit_proof(proof)?,
+            Element::Landscape(ref landscape) => self.visit_landscape(landscape)?,
+            Element::AdjustBox(ref adjust_box) => self.visit_adjust_box(adjust_box)?,
+            Element::WrapFigure(ref figure) => self.visit_wrap_figure(figure)?,
+            Element::ColorBox(ref color_box) => self.visit_color_box(color_box)?,
+            Element::Aligned(ref aligned) => self.visit_aligned(aligned)?,
+            Element::Environment(ref name, ref lines) => self.visit_float(name, lines),
+            // anything in an excluded block never reaches LaTeX's counters
+            Element::Comment {
+                ref name,
+                included: true,
+                ref content,
+            } => self.visit_comment(name, true, content)?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn visit_section(&mut self, section: &Section) -> Result<(), Error> {
+        if self.depth < self.numbered_depth {
+            self.sections.truncate(self.depth + 1);
+            self.sections.resize(self.depth + 1, 0);
+            self.sections[self.depth] += 1;
+
+            if let Some(label) = section.get_label() {
+                let number: Vec<_> = self.sections.iter().map(ToString::to_string).collect();
+                let number = self.within_chapter(number.join("."));
+                self.labels.insert(label.to_string(), number);
+            }
+        }
+
+        self.depth += 1;
+        for element in section.iter() {
+            self.visit_element(element)?;
+        }
+        self.depth -= 1;
+
+        Ok(())
+    }
+
+    fn visit_long_equation(&mut self, equation: &LongEquation) -> Result<(), Error> {
+        self.equation += 1;
+
+        if let Some(label) = equation.get_label() {
+            let number = self.within_chapter(self.equation.to_string());
+            self.labels.insert(label.to_string(), number);
+        }
+
+        Ok(())
+    }
+
+    fn visit_equation(&mut self, equation: &Equation) -> Result<(), Error> {
+        if equation.is_numbered() {
+            self.equation += 1;
+
+            if let Some(label) = equation.get_label() {
+                let number = self.within_chapter(self.equation.to_string());
+                self.labels.insert(label.to_string(), number);
+            }
+        }
+
+        Ok(())
+    }
+}
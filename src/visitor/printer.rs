@@ -1,29 +1,361 @@
-use std::io::Write;
-
+use std::borrow::Cow;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io::{self, Write};
+use std::mem;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::str;
+
+use super::requirements::Requirements;
 use super::Visitor;
-use document::{Document, DocumentClass, Element, Preamble, PreambleElement};
-use equations::{Align, Equation};
+use acronyms::AcronymList;
+use bibliography::ManualBibliography;
+use csv_table::CsvTable;
+use document::{Document, DocumentClass, Element, Package, Preamble, PreambleElement};
+use equations::{Align, Equation, LongEquation, LongEquationKind};
+use escape::escape;
 use failure::Error;
+use layout::{AdjustBox, Aligned, ColorBox, Landscape, PageStyle, WrapFigure};
 use lists::{Item, List};
-use paragraph::{Paragraph, ParagraphElement};
+use paragraph::{self, Paragraph, ParagraphElement};
+use plot::Plot;
+use proof::Proof;
 use section::Section;
 
 /// Print a document to a string.
-pub fn print(doc: &Document) -> Result<String, Error> {
-    let mut buffer = Vec::new();
-    {
-        let mut printer = Printer::new(&mut buffer);
-        printer.visit_document(doc)?;
+pub fn print(doc: &Document) -> Result<String, RenderError> {
+    print_with(doc, &RenderOptions::default())
+}
+
+/// Print a document to a string, using the provided `RenderOptions`.
+///
+/// # Examples
+///
+/// ```rust
+/// use latex::{print_with, Document, DocumentClass, RenderOptions};
+///
+/// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut doc = Document::new(DocumentClass::Article);
+/// doc.push("Hello World");
+///
+/// let options = RenderOptions {
+///     body_only: true,
+///     ..Default::default()
+/// };
+/// assert_eq!(print_with(&doc, &options)?, "Hello World\n");
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// # run().unwrap();
+/// # }
+/// ```
+pub fn print_with(doc: &Document, options: &RenderOptions) -> Result<String, RenderError> {
+    let mut rendered = String::new();
+    Printer::with_options(FmtWriter::new(&mut rendered), options.clone()).visit_document(doc)?;
+    Ok(rendered)
+}
+
+/// Print a document straight into something which implements `fmt::Write`
+/// (e.g. an existing `String`), without rendering it into a buffer first.
+///
+/// ```rust
+/// use latex::{print, write_document, Document, DocumentClass};
+///
+/// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut doc = Document::new(DocumentClass::Article);
+/// doc.push("Hello World");
+///
+/// let mut rendered = String::new();
+/// write_document(&doc, &mut rendered)?;
+/// assert_eq!(rendered, print(&doc)?);
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// # run().unwrap();
+/// # }
+/// ```
+pub fn write_document<W: fmt::Write>(doc: &Document, out: &mut W) -> Result<(), RenderError> {
+    Printer::new(FmtWriter::new(out)).visit_document(doc)?;
+    Ok(())
+}
+
+/// The reasons a document can fail to render.
+#[derive(Debug)]
+pub enum RenderError {
+    /// A file the document includes (e.g. with `Element::InlineFile`)
+    /// couldn't be read.
+    Io {
+        /// The file which was being read.
+        path: PathBuf,
+        /// What went wrong.
+        source: io::Error,
+    },
+    /// The rendered document couldn't be written to its destination.
+    Write(io::Error),
+    /// Something in the document can't be rendered as it is (e.g. a
+    /// reversed page range).
+    Invalid(Error),
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            RenderError::Io {
+                ref path,
+                ref source,
+            } => write!(f, "Unable to read \"{}\": {}", path.display(), source),
+            RenderError::Write(ref e) => write!(f, "Unable to write the document: {}", e),
+            RenderError::Invalid(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for RenderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            RenderError::Io { ref source, .. } => Some(source),
+            RenderError::Write(ref e) => Some(e),
+            RenderError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<Error> for RenderError {
+    /// Recover the `RenderError` (or failed write) behind an error from a
+    /// `Visitor`.
+    fn from(other: Error) -> RenderError {
+        let other = match other.downcast::<RenderError>() {
+            Ok(e) => return e,
+            Err(other) => other,
+        };
+
+        match other.downcast::<io::Error>() {
+            Ok(e) => RenderError::Write(e),
+            Err(other) => RenderError::Invalid(other),
+        }
+    }
+}
+
+/// An adapter which lets a `Printer` write to a `fmt::Write`.
+struct FmtWriter<'a, W: 'a> {
+    out: &'a mut W,
+}
+
+impl<'a, W: fmt::Write> FmtWriter<'a, W> {
+    fn new(out: &'a mut W) -> FmtWriter<'a, W> {
+        FmtWriter { out }
+    }
+}
+
+impl<'a, W: fmt::Write> Write for FmtWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text =
+            str::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.out
+            .write_str(text)
+            .map_err(|_| io::Error::other("Unable to write to the output"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The writer a `Printer` renders into, which translates line endings on the
+/// way to the real `Writer`.
+struct LineEndings<W> {
+    inner: W,
+    line_ending: LineEnding,
+    after_cr: bool,
+}
+
+impl<W: Write> LineEndings<W> {
+    fn new(inner: W, line_ending: LineEnding) -> LineEndings<W> {
+        LineEndings {
+            inner,
+            line_ending,
+            after_cr: false,
+        }
+    }
+}
+
+impl<W: Write> Write for LineEndings<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.line_ending == LineEnding::Lf {
+            return self.inner.write(buf);
+        }
+
+        let mut start = 0;
+        for (i, &b) in buf.iter().enumerate() {
+            // leave any "\r\n"s which are already there alone
+            if b == b'\n' && !self.after_cr {
+                self.inner.write_all(&buf[start..i])?;
+                self.inner.write_all(b"\r")?;
+                start = i;
+            }
+            self.after_cr = b == b'\r';
+        }
+        self.inner.write_all(&buf[start..])?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
     }
+}
 
-    let rendered = String::from_utf8(buffer)?;
+/// Render everything before `\begin{document}`.
+pub(crate) fn preamble_string(doc: &Document) -> Result<String, RenderError> {
+    let mut rendered = String::new();
+    Printer::new(FmtWriter::new(&mut rendered)).write_preamble(doc)?;
     Ok(rendered)
 }
 
+/// Render the document's body, without the `\begin{document}` wrapper.
+pub(crate) fn body_string(doc: &Document) -> Result<String, RenderError> {
+    let mut rendered = String::new();
+    Printer::new(FmtWriter::new(&mut rendered)).write_body(doc)?;
+    Ok(rendered)
+}
+
+/// Get every package the document will import, in the order they're
+/// rendered with the default `RenderOptions`.
+pub(crate) fn required_packages(doc: &Document) -> Result<Vec<Package>, Error> {
+    let preamble = rendered_preamble(doc, &RenderOptions::default())?;
+
+    let packages = in_load_order(&preamble)
+        .into_iter()
+        .filter_map(|item| match *item {
+            PreambleElement::UsePackage {
+                ref package,
+                ref argument,
+            } => Some(Package {
+                name: package.clone(),
+                options: argument.clone(),
+            }),
+            _ => None,
+        })
+        .collect();
+
+    Ok(packages)
+}
+
+/// The preamble as it gets rendered, with any packages the document's
+/// elements require added unless the options turn them off.
+fn rendered_preamble<'a>(
+    doc: &'a Document,
+    options: &RenderOptions,
+) -> Result<Cow<'a, Preamble>, Error> {
+    if options.add_required_packages {
+        let requirements = Requirements::collect(doc)?;
+        Ok(Cow::Owned(requirements.apply_to(&doc.preamble)))
+    } else {
+        Ok(Cow::Borrowed(&doc.preamble))
+    }
+}
+
+/// Options controlling how a `Printer` renders a document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderOptions {
+    /// Only render the document's body, leaving out the `\documentclass`,
+    /// preamble, and the `\begin{document}`/`\end{document}` wrapper.
+    pub body_only: bool,
+    /// Wrap the text of each paragraph so its lines are no longer than this
+    /// many characters (where possible).
+    pub wrap: Option<usize>,
+    /// Start a new line after any raw text which ends in a `%` comment, so
+    /// the comment doesn't swallow whatever is rendered after it.
+    pub terminate_comments: bool,
+    /// The line ending written at the end of each line.
+    pub line_ending: LineEnding,
+    /// Add any packages (and their set-up lines) which the document's
+    /// elements need to the rendered preamble, on top of the ones it
+    /// imports itself. This is on by default; turn it off if the preamble
+    /// is managed somewhere else.
+    pub add_required_packages: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> RenderOptions {
+        RenderOptions {
+            body_only: false,
+            wrap: None,
+            terminate_comments: false,
+            line_ending: LineEnding::default(),
+            add_required_packages: true,
+        }
+    }
+}
+
+/// The characters used to end each line of a rendered document.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineEnding {
+    /// A line feed (`\n`), as used on Unix.
+    #[default]
+    Lf,
+    /// A carriage return and line feed (`\r\n`), as used on Windows.
+    CrLf,
+}
+
+/// Render a single element on its own, wrapped in a minimal `article`
+/// document which imports any packages the element needs.
+///
+/// This is mainly useful for debugging how an element gets rendered.
+pub fn preview(element: &Element) -> Result<String, RenderError> {
+    let mut doc = Document::new(DocumentClass::Article);
+    doc.push(element.clone());
+    print(&doc)
+}
+
+/// Normalize some rendered LaTeX so it can be compared without worrying
+/// about insignificant whitespace (e.g. in tests).
+///
+/// Trailing whitespace is removed from each line, leading and trailing blank
+/// lines are dropped, and runs of blank lines are collapsed into one (LaTeX
+/// treats them all as a single paragraph break).
+///
+/// ```rust
+/// use latex::normalize_latex;
+///
+/// assert_eq!(
+///     normalize_latex("First  \n\n\n\nSecond\n\n"),
+///     normalize_latex("First\n\nSecond"),
+/// );
+/// ```
+pub fn normalize_latex(text: &str) -> String {
+    let mut normalized = String::new();
+    let mut pending_blank = false;
+
+    for line in text.lines().map(str::trim_end) {
+        if line.is_empty() {
+            pending_blank = !normalized.is_empty();
+            continue;
+        }
+
+        if pending_blank {
+            normalized.push('\n');
+            pending_blank = false;
+        }
+        normalized.push_str(line);
+        normalized.push('\n');
+    }
+
+    normalized
+}
+
 /// The type which uses the `Visitor` pattern to visit each node in a document
 /// and write its `tex` representation to a `Writer`.
 pub struct Printer<W> {
-    writer: W,
+    writer: LineEndings<W>,
+    options: RenderOptions,
+    section_depth: usize,
+    hide_todos: bool,
+    endnotes: bool,
+    in_argument: bool,
 }
 
 impl<W> Printer<W>
@@ -32,7 +364,82 @@ where
 {
     /// Create a new `Printer` which will write to the provided `Writer`.
     pub fn new(writer: W) -> Printer<W> {
-        Printer { writer }
+        Printer::with_options(writer, RenderOptions::default())
+    }
+
+    /// Create a new `Printer` which will write to the provided `Writer`
+    /// using custom `RenderOptions`.
+    pub fn with_options(writer: W, options: RenderOptions) -> Printer<W> {
+        Printer {
+            writer: LineEndings::new(writer, options.line_ending),
+            options,
+            section_depth: 0,
+            hide_todos: false,
+            endnotes: false,
+            in_argument: false,
+        }
+    }
+
+    /// Create a `Printer` with the same state as this one, but which writes
+    /// to a different `Writer`. Line endings are left alone, since they're
+    /// translated once the nested output is copied into this `Printer`.
+    fn nested<V: Write>(&self, writer: V) -> Printer<V> {
+        Printer {
+            writer: LineEndings::new(writer, LineEnding::Lf),
+            options: self.options.clone(),
+            section_depth: self.section_depth,
+            hide_todos: self.hide_todos,
+            endnotes: self.endnotes,
+            in_argument: self.in_argument,
+        }
+    }
+
+    /// Write the `\documentclass` line and the preamble, including any
+    /// packages the document's elements require unless the options turn
+    /// them off.
+    fn write_preamble(&mut self, doc: &Document) -> Result<(), Error> {
+        // subfiles take their class options and preamble from the main file
+        if let DocumentClass::Subfile(ref main) = doc.class {
+            writeln!(self.writer, r"\documentclass[{}]{{{}}}", main, doc.class)?;
+            return Ok(());
+        }
+
+        match doc.mode() {
+            Some(mode) => writeln!(self.writer, r"\documentclass[{}]{{{}}}", mode, doc.class)?,
+            None => writeln!(self.writer, r"\documentclass{{{}}}", doc.class)?,
+        }
+
+        let preamble = rendered_preamble(doc, &self.options)?;
+        self.visit_preamble(&preamble)
+    }
+
+    /// Write an element which goes inside a command's argument.
+    fn visit_argument(&mut self, element: &ParagraphElement) -> Result<(), Error> {
+        let in_argument = mem::replace(&mut self.in_argument, true);
+        let result = self.visit_paragraph_element(element);
+        self.in_argument = in_argument;
+        result
+    }
+
+    /// Write each of the document's elements.
+    fn write_body(&mut self, doc: &Document) -> Result<(), Error> {
+        self.hide_todos = doc.todos_hidden();
+        self.endnotes = doc.endnotes;
+
+        if doc.line_numbers {
+            writeln!(self.writer, r"\linenumbers")?;
+        }
+
+        for element in doc.iter() {
+            if let Element::Section(_) = *element {
+                if doc.section_page_break {
+                    writeln!(self.writer, r"\clearpage")?;
+                }
+            }
+            self.visit_element(element)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -41,52 +448,127 @@ where
     W: Write,
 {
     fn visit_document(&mut self, doc: &Document) -> Result<(), Error> {
-        match doc.class {
-            // only go through childs if we have a partial document
-            DocumentClass::Part => {
-                for element in doc.iter() {
-                    self.visit_element(element)?;
-                }
-            }
+        // only go through childs if we have a partial document, or were only
+        // asked for the body
+        if doc.class == DocumentClass::Part || self.options.body_only {
+            self.write_body(doc)?;
+        } else {
             // write a full document
-            _ => {
-                writeln!(self.writer, r"\documentclass{{{}}}", doc.class)?;
-
-                self.visit_preamble(&doc.preamble)?;
-
-                writeln!(self.writer, r"\begin{{document}}")?;
-
-                for element in doc.iter() {
-                    self.visit_element(element)?;
-                }
-
-                writeln!(self.writer, r"\end{{document}}")?;
-            }
+            self.write_preamble(doc)?;
+            writeln!(self.writer, r"\begin{{document}}")?;
+            self.write_body(doc)?;
+            writeln!(self.writer, r"\end{{document}}")?;
         }
         Ok(())
     }
 
     fn visit_paragraph(&mut self, para: &Paragraph) -> Result<(), Error> {
-        for elem in para.iter() {
-            self.visit_paragraph_element(elem)?;
+        match self.options.wrap {
+            Some(width) => {
+                let mut buffer = Vec::new();
+                {
+                    let mut printer = self.nested(&mut buffer);
+                    for elem in para.iter() {
+                        printer.visit_paragraph_element(elem)?;
+                    }
+                }
+
+                let text = String::from_utf8(buffer)?;
+                writeln!(self.writer, "{}", wrap_text(&text, width))?;
+            }
+            None => {
+                for elem in para.iter() {
+                    self.visit_paragraph_element(elem)?;
+                }
+
+                // a terminated comment has already ended the last line
+                let terminated = self.options.terminate_comments
+                    && para.iter().last().is_some_and(|elem| match *elem {
+                        ParagraphElement::Plain(ref s) => ends_in_comment(s),
+                        _ => false,
+                    });
+                if !terminated {
+                    writeln!(self.writer)?;
+                }
+            }
         }
-        writeln!(self.writer)?;
 
         Ok(())
     }
 
     fn visit_paragraph_element(&mut self, element: &ParagraphElement) -> Result<(), Error> {
         match *element {
-            ParagraphElement::Plain(ref s) => write!(self.writer, "{}", s)?,
+            ParagraphElement::Plain(ref s) => {
+                write!(self.writer, "{}", s)?;
+                if self.options.terminate_comments && ends_in_comment(s) {
+                    writeln!(self.writer)?;
+                }
+            }
             ParagraphElement::InlineMath(ref s) => write!(self.writer, "${}$", s)?,
+            ParagraphElement::Acro(ref key) => write!(self.writer, r"\ac{{{}}}", key)?,
+            // \verb doesn't work inside another command's argument
+            ParagraphElement::Verb(ref s) if self.in_argument => {
+                write!(self.writer, r"\texttt{{{}}}", escape(s))?
+            }
+            ParagraphElement::Verb(ref s) => match paragraph::verb_delimiter(s) {
+                Some(d) => write!(self.writer, r"\verb{}{}{}", d, s, d)?,
+                None => bail!("Unable to find a \\verb delimiter which isn't in \"{}\"", s),
+            },
+            ParagraphElement::Underline(ref s) => write!(self.writer, r"\uline{{{}}}", escape(s))?,
+            ParagraphElement::Strikeout(ref s) => write!(self.writer, r"\sout{{{}}}", escape(s))?,
+            ParagraphElement::Highlight(ref s) => write!(self.writer, r"\hl{{{}}}", escape(s))?,
+            ParagraphElement::Code(ref s) => write!(self.writer, r"\texttt{{{}}}", escape(s))?,
+            ParagraphElement::SiUnit {
+                ref value,
+                ref unit,
+            } => {
+                paragraph::check_si_value(value)?;
+                write!(self.writer, r"\SI{{{}}}{{{}}}", value, unit)?
+            }
+            ParagraphElement::Nomenclature {
+                ref symbol,
+                ref description,
+            } => write!(
+                self.writer,
+                r"\nomenclature{{{}}}{{{}}}",
+                symbol, description
+            )?,
+            ParagraphElement::Todo(ref s) => {
+                if !self.hide_todos {
+                    write!(self.writer, r"\todo{{{}}}", s)?;
+                }
+            }
+            ParagraphElement::PdfComment(ref s) => write!(self.writer, r"\pdfcomment{{{}}}", s)?,
             ParagraphElement::Bold(ref e) => {
                 write!(self.writer, r"\textbf{{")?;
-                self.visit_paragraph_element(e)?;
+                self.visit_argument(e)?;
                 write!(self.writer, "}}")?;
             }
             ParagraphElement::Italic(ref e) => {
                 write!(self.writer, r"\textit{{")?;
-                self.visit_paragraph_element(e)?;
+                self.visit_argument(e)?;
+                write!(self.writer, "}}")?;
+            }
+            ParagraphElement::Footnote(ref e) => {
+                let command = if self.endnotes { "endnote" } else { "footnote" };
+                write!(self.writer, r"\{}{{", command)?;
+                self.visit_argument(e)?;
+                write!(self.writer, "}}")?;
+            }
+            ParagraphElement::Enquote(ref s) => {
+                write!(self.writer, r"\enquote{{")?;
+                // pairs of straight quotes become nested quotes
+                if s.matches('"').count() % 2 == 0 {
+                    for (i, part) in s.split('"').enumerate() {
+                        if i % 2 == 1 {
+                            write!(self.writer, r"\enquote{{{}}}", escape(part))?;
+                        } else {
+                            write!(self.writer, "{}", escape(part))?;
+                        }
+                    }
+                } else {
+                    write!(self.writer, "{}", escape(s))?;
+                }
                 write!(self.writer, "}}")?;
             }
         }
@@ -95,7 +577,7 @@ where
     }
 
     fn visit_preamble(&mut self, preamble: &Preamble) -> Result<(), Error> {
-        for item in preamble.iter() {
+        for item in in_load_order(preamble) {
             match item {
                 PreambleElement::UsePackage {
                     package: pkg,
@@ -110,8 +592,18 @@ where
                     args_num,
                     default_arg,
                     definition
+                }
+                | PreambleElement::RenewCommand {
+                    name,
+                    args_num,
+                    default_arg,
+                    definition,
                 } => {
-                    write!(self.writer, r"\newcommand{{\{}}}", name)?;
+                    let command = match item {
+                        PreambleElement::RenewCommand { .. } => "renewcommand",
+                        _ => "newcommand",
+                    };
+                    write!(self.writer, r"\{}{{\{}}}", command, name)?;
                     if let Some(num) = args_num {
                         write!(self.writer, r"[{}]", num)?;
                     }
@@ -122,11 +614,64 @@ where
                     writeln!(self.writer, "{}", definition)?;
                     writeln!(self.writer, r"}}")?;
                 },
+                PreambleElement::NewEnvironment {
+                    name,
+                    args_num,
+                    begin,
+                    end,
+                } => {
+                    write!(self.writer, r"\newenvironment{{{}}}", name)?;
+                    if let Some(num) = args_num {
+                        write!(self.writer, r"[{}]", num)?;
+                    }
+                    writeln!(self.writer, r"{{{}}}{{{}}}", begin, end)?;
+                }
+                PreambleElement::SetLength { register, value } => {
+                    writeln!(self.writer, r"\setlength{{\{}}}{{{}}}", register, value)?
+                }
+                PreambleElement::DeclareMathOperator {
+                    name,
+                    rendered,
+                    starred,
+                } => writeln!(
+                    self.writer,
+                    r"\DeclareMathOperator{}{{\{}}}{{{}}}",
+                    if *starred { "*" } else { "" },
+                    name,
+                    rendered
+                )?,
+                PreambleElement::NewDocumentCommand {
+                    name,
+                    arg_spec,
+                    body,
+                } => writeln!(
+                    self.writer,
+                    r"\NewDocumentCommand{{\{}}}{{{}}}{{{}}}",
+                    name, arg_spec, body
+                )?,
+                PreambleElement::PageStyle(style) => {
+                    writeln!(self.writer, r"\pagestyle{{{}}}", style)?;
+
+                    if let PageStyle::Fancy(ref headers) = *style {
+                        writeln!(self.writer, r"\fancyhf{{}}")?;
+                        for (command, position, text) in headers.iter() {
+                            writeln!(self.writer, r"\{}[{}]{{{}}}", command, position, text)?;
+                        }
+                    }
+                }
+                PreambleElement::HyperSetup(options) => {
+                    let options = options.to_string();
+                    if !options.is_empty() {
+                        writeln!(self.writer, r"\hypersetup{{{}}}", options)?;
+                    }
+                }
                 PreambleElement::UserDefined(s) => writeln!(self.writer, r"{}", s)?,
             }
         }
 
-        if !preamble.is_empty() && (preamble.title.is_some() || preamble.author.is_some()) {
+        let has_title_fields =
+            preamble.title.is_some() || preamble.author.is_some() || preamble.date.is_some();
+        if !preamble.is_empty() && has_title_fields {
             writeln!(self.writer)?;
         }
 
@@ -136,17 +681,28 @@ where
         if let Some(ref author) = preamble.author {
             writeln!(self.writer, r"\author{{{}}}", author)?;
         }
+        if let Some(ref date) = preamble.date {
+            writeln!(self.writer, r"\date{{{}}}", date)?;
+        }
 
         Ok(())
     }
 
     fn visit_list(&mut self, list: &List) -> Result<(), Error> {
         let env = list.kind.environment_name();
+        let options = list.options();
 
-        writeln!(self.writer, r"\begin{{{}}}", env)?;
+        if options.is_empty() {
+            writeln!(self.writer, r"\begin{{{}}}", env)?;
+        } else {
+            writeln!(self.writer, r"\begin{{{}}}[{}]", env, options.join(","))?;
+        }
 
-        for item in list.iter() {
-            self.visit_list_item(item)?;
+        for (i, item) in list.iter().enumerate() {
+            match list.overlay(i) {
+                Some(overlay) => writeln!(self.writer, r"\item<{}>{{{}}}", overlay, item.0)?,
+                None => self.visit_list_item(item)?,
+            }
         }
 
         writeln!(self.writer, r"\end{{{}}}", env)?;
@@ -163,11 +719,67 @@ where
         match *element {
             Element::Para(ref p) => self.visit_paragraph(p)?,
             Element::Section(ref s) => self.visit_section(s)?,
+            Element::Part { ref title, starred } => writeln!(
+                self.writer,
+                r"\part{}{{{}}}",
+                if starred { "*" } else { "" },
+                title
+            )?,
+            Element::Chapter { ref title, starred } => writeln!(
+                self.writer,
+                r"\chapter{}{{{}}}",
+                if starred { "*" } else { "" },
+                title
+            )?,
             Element::TableOfContents => writeln!(self.writer, r"\tableofcontents")?,
             Element::TitlePage => writeln!(self.writer, r"\maketitle")?,
             Element::ClearPage => writeln!(self.writer, r"\clearpage")?,
+            Element::ParagraphBreak => writeln!(self.writer)?,
+            Element::NewGeometry(ref options) => {
+                writeln!(self.writer, r"\newgeometry{{{}}}", options)?
+            }
+            Element::RestoreGeometry => writeln!(self.writer, r"\restoregeometry")?,
+            Element::ThisPageStyle(ref style) => {
+                writeln!(self.writer, r"\thispagestyle{{{}}}", style)?
+            }
+            Element::PageNumbering(style) => writeln!(self.writer, r"\pagenumbering{{{}}}", style)?,
+            Element::SetCounter { ref name, value } => {
+                writeln!(self.writer, r"\setcounter{{{}}}{{{}}}", name, value)?
+            }
+            Element::AddToCounter { ref name, delta } => {
+                writeln!(self.writer, r"\addtocounter{{{}}}{{{}}}", name, delta)?
+            }
+            Element::PrintEndnotes => writeln!(self.writer, r"\theendnotes")?,
+            Element::PrintShorthands => writeln!(self.writer, r"\printshorthands")?,
+            Element::PrintNomenclature => writeln!(self.writer, r"\printnomenclature")?,
             Element::UserDefined(ref s) => writeln!(self.writer, "{}", s)?,
             Element::Align(ref equations) => self.visit_align(equations)?,
+            Element::LongEquation(ref equation) => self.visit_long_equation(equation)?,
+            Element::Plot(ref plot) => self.visit_plot(plot)?,
+            Element::CsvTable(ref table) => self.visit_csv_table(table)?,
+            Element::Proof(ref proof) => self.visit_proof(proof)?,
+            Element::Landscape(ref landscape) => self.visit_landscape(landscape)?,
+            Element::AdjustBox(ref adjust_box) => self.visit_adjust_box(adjust_box)?,
+            Element::WrapFigure(ref figure) => self.visit_wrap_figure(figure)?,
+            Element::ColorBox(ref color_box) => self.visit_color_box(color_box)?,
+            Element::Aligned(ref aligned) => self.visit_aligned(aligned)?,
+            Element::Lipsum(ref paragraphs) => self.visit_lipsum(paragraphs.as_ref())?,
+            Element::IncludePdf {
+                ref path,
+                ref pages,
+            } => self.visit_include_pdf(path, pages.as_ref())?,
+            Element::ManualBibliography(ref bib) => self.visit_manual_bibliography(bib)?,
+            Element::Acronyms(ref acronyms) => self.visit_acronyms(acronyms)?,
+            Element::Comment {
+                ref name,
+                included,
+                ref content,
+            } => self.visit_comment(name, included, content)?,
+            Element::TikzPicture(ref body) => {
+                writeln!(self.writer, r"\begin{{tikzpicture}}")?;
+                writeln!(self.writer, "{}", body)?;
+                writeln!(self.writer, r"\end{{tikzpicture}}")?;
+            }
 
             Element::Environment(ref name, ref lines) => {
                 writeln!(self.writer, r"\begin{{{}}}", name)?;
@@ -178,6 +790,21 @@ where
             }
             Element::List(ref list) => self.visit_list(list)?,
             Element::Input(ref s) => writeln!(self.writer, "\\input{{{}}}", s)?,
+            Element::Subfile(ref s) => writeln!(self.writer, r"\subfile{{{}}}", s)?,
+            Element::Import { ref dir, ref file } => {
+                writeln!(self.writer, r"\import{{{}}}{{{}}}", dir, file)?
+            }
+            Element::InlineFile(ref path) => {
+                let contents = fs::read_to_string(path).map_err(|source| RenderError::Io {
+                    path: path.clone(),
+                    source,
+                })?;
+
+                write!(self.writer, "{}", contents)?;
+                if !contents.ends_with('\n') {
+                    writeln!(self.writer)?;
+                }
+            }
 
             Element::_Other => unreachable!(),
         }
@@ -186,399 +813,2613 @@ where
     }
 
     fn visit_section(&mut self, section: &Section) -> Result<(), Error> {
-        writeln!(self.writer, r"\section{{{}}}", section.name)?;
+        writeln!(
+            self.writer,
+            r"\{}{{{}}}",
+            sectioning_command(self.section_depth),
+            section.name
+        )?;
+        if let Some(label) = section.get_label() {
+            writeln!(self.writer, r"\label{{{}}}", label)?;
+        }
 
         if !section.is_empty() {
             // Make sure there's space between the \section{...} and the next line
             writeln!(self.writer)?;
         }
 
+        self.section_depth += 1;
         for element in section.iter() {
             self.visit_element(element)?;
             // LaTeX needs an empty line between paragraphs/elements otherwise
             // it'll automatically concatenate them together
             writeln!(self.writer)?;
         }
+        self.section_depth -= 1;
 
         Ok(())
     }
 
-    fn visit_equation(&mut self, equation: &Equation) -> Result<(), Error> {
-        write!(self.writer, r"{}", equation.get_text())?;
-
-        if let Some(ref label) = equation.get_label() {
-            write!(self.writer, r" \label{{{}}}", label)?;
+    fn visit_plot(&mut self, plot: &Plot) -> Result<(), Error> {
+        let mut labels = Vec::new();
+        if let Some(label) = plot.get_x_label() {
+            labels.push(format!("xlabel={{{}}}", label));
         }
-        if !equation.is_numbered() {
-            write!(self.writer, r" \nonumber")?;
+        if let Some(label) = plot.get_y_label() {
+            labels.push(format!("ylabel={{{}}}", label));
         }
 
-        writeln!(self.writer, r" \\")?;
+        writeln!(self.writer, r"\begin{{tikzpicture}}")?;
+        if labels.is_empty() {
+            writeln!(self.writer, r"\begin{{axis}}")?;
+        } else {
+            writeln!(self.writer, r"\begin{{axis}}[{}]", labels.join(", "))?;
+        }
+
+        let coordinates: Vec<_> = plot
+            .iter()
+            .map(|&(x, y)| format!("({},{})", x, y))
+            .collect();
+        writeln!(
+            self.writer,
+            r"\addplot coordinates {{{}}};",
+            coordinates.join(" ")
+        )?;
+
+        writeln!(self.writer, r"\end{{axis}}")?;
+        writeln!(self.writer, r"\end{{tikzpicture}}")?;
+
         Ok(())
     }
 
-    fn visit_align(&mut self, align: &Align) -> Result<(), Error> {
-        writeln!(self.writer, r"\begin{{align}}")?;
+    fn visit_csv_table(&mut self, table: &CsvTable) -> Result<(), Error> {
+        let alignment = "l".repeat(table.iter().len());
+        let headings: Vec<_> = table.iter().map(String::as_str).collect();
+        // csvsimple names each column's macro with a lowercase roman numeral
+        let cells: Vec<_> = (1..=headings.len())
+            .map(|column| format!(r"\csvcol{}", lower_roman(column)))
+            .collect();
+
+        writeln!(
+            self.writer,
+            r"\csvreader[tabular={},table head={}\\]{{{}}}{{}}{{{}}}",
+            alignment,
+            headings.join(" & "),
+            table.path().display(),
+            cells.join(" & ")
+        )?;
 
-        for item in align.iter() {
-            self.visit_equation(item)?;
+        Ok(())
+    }
+
+    fn visit_proof(&mut self, proof: &Proof) -> Result<(), Error> {
+        match proof.get_phrase() {
+            Some(phrase) => writeln!(self.writer, r"\begin{{proof}}[{}]", phrase)?,
+            None => writeln!(self.writer, r"\begin{{proof}}")?,
         }
 
-        writeln!(self.writer, r"\end{{align}}")?;
+        for element in proof.iter() {
+            self.visit_element(element)?;
+        }
 
+        writeln!(self.writer, r"\end{{proof}}")?;
         Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use self::ParagraphElement::*;
-    use super::*;
-    use {Align, DocumentClass, Equation, ListKind, Paragraph, Section};
+    fn visit_landscape(&mut self, landscape: &Landscape) -> Result<(), Error> {
+        writeln!(self.writer, r"\begin{{landscape}}")?;
+        for element in landscape.iter() {
+            self.visit_element(element)?;
+        }
+        writeln!(self.writer, r"\end{{landscape}}")?;
 
-    #[test]
-    fn create_simple_paragraph() {
-        let should_be = "Hello World\n";
-        let mut buffer = Vec::new();
+        Ok(())
+    }
 
-        let mut para = Paragraph::new();
-        para.push_text("Hello World");
+    fn visit_adjust_box(&mut self, adjust_box: &AdjustBox) -> Result<(), Error> {
+        writeln!(
+            self.writer,
+            r"\adjustbox{{max width={}}}{{",
+            adjust_box.max_width
+        )?;
+        for element in adjust_box.iter() {
+            self.visit_element(element)?;
+        }
+        writeln!(self.writer, "}}")?;
 
-        {
-            let mut printer = Printer::new(&mut buffer);
-            printer.visit_paragraph(&para).unwrap();
+        Ok(())
+    }
+
+    fn visit_wrap_figure(&mut self, figure: &WrapFigure) -> Result<(), Error> {
+        writeln!(
+            self.writer,
+            r"\begin{{wrapfigure}}{{{}}}{{{}}}",
+            figure.side, figure.width
+        )?;
+        for element in figure.iter() {
+            self.visit_element(element)?;
         }
+        writeln!(self.writer, r"\end{{wrapfigure}}")?;
 
-        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+        Ok(())
     }
 
-    #[test]
-    fn paragraph_with_bold_text() {
-        let should_be = "Hello \\textbf{World}\n";
-        let mut buffer = Vec::new();
+    fn visit_color_box(&mut self, color_box: &ColorBox) -> Result<(), Error> {
+        let options = color_box.options();
+        if options.is_empty() {
+            writeln!(self.writer, r"\begin{{tcolorbox}}")?;
+        } else {
+            writeln!(self.writer, r"\begin{{tcolorbox}}[{}]", options)?;
+        }
+        for element in color_box.iter() {
+            self.visit_element(element)?;
+        }
+        writeln!(self.writer, r"\end{{tcolorbox}}")?;
 
-        let mut para = Paragraph::new();
-        para.push_text("Hello ");
-        para.push(Bold(Box::new(Plain("World".to_string()))));
+        Ok(())
+    }
 
-        {
-            let mut printer = Printer::new(&mut buffer);
-            printer.visit_paragraph(&para).unwrap();
+    fn visit_aligned(&mut self, aligned: &Aligned) -> Result<(), Error> {
+        writeln!(self.writer, r"\begin{{{}}}", aligned.alignment)?;
+        for element in aligned.iter() {
+            self.visit_element(element)?;
         }
+        writeln!(self.writer, r"\end{{{}}}", aligned.alignment)?;
 
-        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+        Ok(())
     }
 
-    #[test]
-    fn paragraph_with_italic_text() {
-        let should_be = "Hello \\textit{World}\n";
-        let mut buffer = Vec::new();
+    fn visit_comment(&mut self, name: &str, _: bool, content: &[Element]) -> Result<(), Error> {
+        writeln!(self.writer, r"\begin{{{}}}", name)?;
+        for element in content {
+            self.visit_element(element)?;
+        }
+        writeln!(self.writer, r"\end{{{}}}", name)?;
 
-        let mut para = Paragraph::new();
-        para.push_text("Hello ");
-        para.push(Italic(Box::new(Plain("World".to_string()))));
+        Ok(())
+    }
 
-        {
-            let mut printer = Printer::new(&mut buffer);
-            printer.visit_paragraph(&para).unwrap();
+    fn visit_lipsum(&mut self, paragraphs: Option<&RangeInclusive<usize>>) -> Result<(), Error> {
+        match paragraphs {
+            Some(range) if range.start() > range.end() => bail!(
+                "The lipsum paragraphs {}-{} are the wrong way around",
+                range.start(),
+                range.end()
+            ),
+            Some(range) if range.start() == range.end() => {
+                writeln!(self.writer, r"\lipsum[{}]", range.start())?
+            }
+            Some(range) => writeln!(self.writer, r"\lipsum[{}-{}]", range.start(), range.end())?,
+            None => writeln!(self.writer, r"\lipsum")?,
         }
 
-        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+        Ok(())
     }
 
-    #[test]
-    fn inline_code() {
-        let should_be = "Hello $\\lambda$ World!\n";
-        let mut buffer = Vec::new();
+    fn visit_include_pdf(
+        &mut self,
+        path: &Path,
+        pages: Option<&RangeInclusive<usize>>,
+    ) -> Result<(), Error> {
+        let pages = match pages {
+            Some(range) if range.start() > range.end() => bail!(
+                "The pages {}-{} of {} are the wrong way around",
+                range.start(),
+                range.end(),
+                path.display()
+            ),
+            Some(range) if range.start() == range.end() => range.start().to_string(),
+            Some(range) => format!("{{{}-{}}}", range.start(), range.end()),
+            // a lone dash means every page
+            None => "-".to_string(),
+        };
+        writeln!(
+            self.writer,
+            r"\includepdf[pages={}]{{{}}}",
+            pages,
+            path.display()
+        )?;
 
-        let mut para = Paragraph::new();
-        para.push_text("Hello ")
-            .push(InlineMath(r"\lambda".to_string()))
-            .push_text(" World!");
+        Ok(())
+    }
 
-        {
-            let mut printer = Printer::new(&mut buffer);
-            printer.visit_paragraph(&para).unwrap();
+    fn visit_manual_bibliography(&mut self, bib: &ManualBibliography) -> Result<(), Error> {
+        writeln!(
+            self.writer,
+            r"\begin{{thebibliography}}{{{}}}",
+            bib.widest_label()
+        )?;
+        for (key, text) in bib.iter() {
+            writeln!(self.writer, r"\bibitem{{{}}} {}", key, text)?;
         }
+        writeln!(self.writer, r"\end{{thebibliography}}")?;
 
-        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+        Ok(())
     }
 
-    #[test]
+    fn visit_acronyms(&mut self, acronyms: &AcronymList) -> Result<(), Error> {
+        writeln!(self.writer, r"\begin{{acronym}}")?;
+        for (key, short, long) in acronyms.iter() {
+            writeln!(self.writer, r"\acro{{{}}}[{}]{{{}}}", key, short, long)?;
+        }
+        writeln!(self.writer, r"\end{{acronym}}")?;
+
+        Ok(())
+    }
+
+    fn visit_equation(&mut self, equation: &Equation) -> Result<(), Error> {
+        write!(self.writer, r"{}", equation.get_text())?;
+
+        if let Some(ref label) = equation.get_label() {
+            write!(self.writer, r" \label{{{}}}", label)?;
+        }
+        if !equation.is_numbered() {
+            write!(self.writer, r" \nonumber")?;
+        }
+
+        writeln!(self.writer, r" \\")?;
+        Ok(())
+    }
+
+    fn visit_align(&mut self, align: &Align) -> Result<(), Error> {
+        writeln!(self.writer, r"\begin{{align}}")?;
+
+        for item in align.iter() {
+            self.visit_equation(item)?;
+        }
+
+        writeln!(self.writer, r"\end{{align}}")?;
+
+        Ok(())
+    }
+
+    fn visit_long_equation(&mut self, equation: &LongEquation) -> Result<(), Error> {
+        let environments: &[&str] = match equation.kind {
+            LongEquationKind::Split => &["equation", "split"],
+            LongEquationKind::Multline => &["multline"],
+        };
+
+        writeln!(self.writer, r"\begin{{{}}}", environments[0])?;
+        if let Some(label) = equation.get_label() {
+            writeln!(self.writer, r"\label{{{}}}", label)?;
+        }
+        for env in &environments[1..] {
+            writeln!(self.writer, r"\begin{{{}}}", env)?;
+        }
+
+        let lines: Vec<_> = equation.iter().map(String::as_str).collect();
+        if !lines.is_empty() {
+            writeln!(self.writer, "{}", lines.join(" \\\\\n"))?;
+        }
+
+        for env in environments.iter().rev() {
+            writeln!(self.writer, r"\end{{{}}}", env)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Greedily break some text into lines at most `width` characters long,
+/// splitting at spaces. Words longer than `width` get a line to themselves.
+///
+/// A `\verb` span is never split, and a `%` comment always stays at the end
+/// of its line. Both are copied exactly as they were written.
+fn wrap_text(text: &str, width: usize) -> String {
+    let mut wrapped = String::new();
+    let mut line_length = 0;
+    let mut after_comment = false;
+
+    for (word, is_comment) in wrap_units(text) {
+        let word_length = word.chars().count();
+
+        if after_comment || (line_length > 0 && line_length + 1 + word_length > width) {
+            wrapped.push('\n');
+            line_length = 0;
+        } else if line_length > 0 {
+            wrapped.push(' ');
+            line_length += 1;
+        }
+
+        wrapped.push_str(word);
+        line_length += word_length;
+        after_comment = is_comment;
+    }
+
+    wrapped
+}
+
+/// Split some text into the pieces `wrap_text()` may put a line break
+/// between, noting which ones end in a comment.
+fn wrap_units(text: &str) -> Vec<(&str, bool)> {
+    let mut units = Vec::new();
+    let mut start = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            ' ' => {
+                if let Some(s) = start.take() {
+                    units.push((&text[s..i], false));
+                }
+                continue;
+            }
+            '%' => {
+                let s = start.take().unwrap_or(i);
+                let end = text[i..].find('\n').map_or(text.len(), |n| i + n);
+                units.push((&text[s..end], true));
+                // skip the rest of the comment, along with the newline
+                while chars.next_if(|&(j, _)| j <= end).is_some() {}
+                continue;
+            }
+            _ => {}
+        }
+
+        start.get_or_insert(i);
+        if c != '\\' {
+            continue;
+        }
+
+        if text[i..].starts_with(r"\verb") {
+            // skip to the closing delimiter, spaces and all
+            let rest = &text[i + r"\verb".len()..];
+            let rest = rest.strip_prefix('*').unwrap_or(rest);
+            if let Some(delimiter) = rest.chars().next() {
+                let body = text.len() - rest.len() + delimiter.len_utf8();
+                let end = text[body..]
+                    .find(delimiter)
+                    .map_or(text.len(), |n| body + n + delimiter.len_utf8());
+                while chars.next_if(|&(j, _)| j < end).is_some() {}
+            }
+        } else {
+            // an escaped character (e.g. "\%") is part of the word
+            chars.next();
+        }
+    }
+
+    if let Some(s) = start {
+        units.push((&text[s..], false));
+    }
+
+    units
+}
+
+/// Packages which need to be loaded after (almost) everything else, in the
+/// order they must appear.
+const LOAD_LAST: &[&str] = &["hyperref", "cleveref", "bookmark"];
+
+/// Get the preamble's elements, with the packages rearranged so anything in
+/// `LOAD_LAST` is imported after the other packages.
+///
+/// Only the packages are reordered amongst themselves, every other element
+/// keeps its original position. The exception is `\hypersetup`, which needs
+/// `hyperref` to be loaded first and so comes straight after the packages.
+fn in_load_order(preamble: &Preamble) -> Vec<&PreambleElement> {
+    let (hyper_setups, mut items): (Vec<_>, Vec<_>) = preamble
+        .iter()
+        .partition(|item| matches!(**item, PreambleElement::HyperSetup(_)));
+
+    let slots: Vec<usize> = items
+        .iter()
+        .enumerate()
+        .filter(|&(_, item)| matches!(**item, PreambleElement::UsePackage { .. }))
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut packages: Vec<_> = slots.iter().map(|&i| items[i]).collect();
+    packages.sort_by_key(|item| match **item {
+        PreambleElement::UsePackage { ref package, .. } => LOAD_LAST
+            .iter()
+            .position(|p| p == package)
+            .map_or(0, |i| i + 1),
+        _ => 0,
+    });
+
+    for (&slot, package) in slots.iter().zip(packages) {
+        items[slot] = package;
+    }
+
+    let after_packages = slots.last().map_or(0, |&i| i + 1);
+    items.splice(after_packages..after_packages, hyper_setups);
+
+    items
+}
+
+/// The sectioning command used for a section nested `depth` levels deep.
+fn sectioning_command(depth: usize) -> &'static str {
+    match depth {
+        0 => "section",
+        1 => "subsection",
+        2 => "subsubsection",
+        3 => "paragraph",
+        _ => "subparagraph",
+    }
+}
+
+/// Does the last line of some raw text contain a `%` which isn't escaped?
+fn ends_in_comment(text: &str) -> bool {
+    let last_line = text.rsplit('\n').next().unwrap_or("");
+    let mut escaped = false;
+
+    for c in last_line.chars() {
+        match c {
+            '%' if !escaped => return true,
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+
+    false
+}
+
+/// Write a number as lowercase roman numerals (e.g. `14` is `xiv`).
+fn lower_roman(mut n: usize) -> String {
+    const NUMERALS: &[(usize, &str)] = &[
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+
+    let mut numeral = String::new();
+    for &(value, letters) in NUMERALS {
+        while n >= value {
+            numeral.push_str(letters);
+            n -= value;
+        }
+    }
+
+    numeral
+}
+
+#[cfg(test)]
+mod tests {
+    use self::ParagraphElement::*;
+    use super::*;
+    use {
+        Abstract, Align, Aligned, Alignment, BiblatexOptions, Cases, DocumentClass, DocumentMode,
+        Equation, FancyHeaders, HyperSetup, Keywords, LineSpacing, ListKind, Matrix, MatrixStyle,
+        MintedOptions, NumberStyle, Paragraph, Section, ThreePartTable, TitlePageBuilder, TocStyle,
+        WrapSide,
+    };
+
+    #[test]
+    fn create_simple_paragraph() {
+        let should_be = "Hello World\n";
+        let mut buffer = Vec::new();
+
+        let mut para = Paragraph::new();
+        para.push_text("Hello World");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_paragraph(&para).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn paragraph_with_bold_text() {
+        let should_be = "Hello \\textbf{World}\n";
+        let mut buffer = Vec::new();
+
+        let mut para = Paragraph::new();
+        para.push_text("Hello ");
+        para.push(Bold(Box::new(Plain("World".to_string()))));
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_paragraph(&para).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn paragraph_with_italic_text() {
+        let should_be = "Hello \\textit{World}\n";
+        let mut buffer = Vec::new();
+
+        let mut para = Paragraph::new();
+        para.push_text("Hello ");
+        para.push(Italic(Box::new(Plain("World".to_string()))));
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_paragraph(&para).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn paragraph_with_nested_quotes() {
+        let should_be = "He wrote \\enquote{She said \\enquote{50\\% \\& \\#1}}\n";
+        let mut buffer = Vec::new();
+
+        let mut para = Paragraph::new();
+        para.push_text("He wrote ")
+            .push(ParagraphElement::enquote(r#"She said "50% & #1""#));
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_paragraph(&para).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn quotes_import_csquotes() {
+        let mut doc = Document::new(DocumentClass::Article);
+        let mut para = Paragraph::new();
+        para.push(ParagraphElement::enquote("Hello"));
+        doc.push(para);
+
+        assert!(print(&doc).unwrap().contains("\\usepackage{csquotes}\n"));
+    }
+
+    #[test]
+    fn inline_code() {
+        let should_be = "Hello $\\lambda$ World!\n";
+        let mut buffer = Vec::new();
+
+        let mut para = Paragraph::new();
+        para.push_text("Hello ")
+            .push(InlineMath(r"\lambda".to_string()))
+            .push_text(" World!");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_paragraph(&para).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn code_is_escaped() {
+        let should_be = "See \\texttt{my\\_file.rs}.\n";
+        let mut buffer = Vec::new();
+
+        let mut para = Paragraph::new();
+        para.push_text("See ")
+            .push(Code("my_file.rs".to_string()))
+            .push_text(".");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_paragraph(&para).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn two_by_two_bmatrix() {
+        let matrix = Matrix::new(
+            vec![
+                vec!["a".to_string(), "b".to_string()],
+                vec!["c".to_string(), "d".to_string()],
+            ],
+            MatrixStyle::Brackets,
+        );
+        let mut doc = Document::new(DocumentClass::Article);
+        let mut para = Paragraph::new();
+        para.push(InlineMath(format!("A = {}", matrix)));
+        doc.push(para);
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{amsmath}\n"));
+        assert!(rendered.contains(r"$A = \begin{bmatrix} a & b \\ c & d \end{bmatrix}$"));
+    }
+
+    #[test]
+    fn two_case_piecewise_function() {
+        let mut cases = Cases::new();
+        cases.push("1", "x > 0").push("0", r"\text{otherwise}");
+        let mut doc = Document::new(DocumentClass::Article);
+        let mut para = Paragraph::new();
+        para.push(InlineMath(format!("H(x) = {}", cases)));
+        doc.push(para);
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{amsmath}\n"));
+        assert!(rendered
+            .contains(r"$H(x) = \begin{cases} 1 & x > 0 \\ 0 & \text{otherwise} \end{cases}$"));
+    }
+
+    #[test]
+    fn underlined_text() {
+        let mut doc = Document::new(DocumentClass::Article);
+        let mut para = Paragraph::new();
+        para.push_text("Some ")
+            .push(Underline("added 10%".to_string()))
+            .push_text(" text");
+        doc.push(para);
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage[normalem]{ulem}\n"));
+        assert!(rendered.contains("Some \\uline{added 10\\%} text\n"));
+    }
+
+    #[test]
+    fn struck_out_text() {
+        let mut doc = Document::new(DocumentClass::Article);
+        let mut para = Paragraph::new();
+        para.push_text("Some ")
+            .push(Strikeout("removed & gone".to_string()))
+            .push_text(" text");
+        doc.push(para);
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage[normalem]{ulem}\n"));
+        assert!(rendered.contains("Some \\sout{removed \\& gone} text\n"));
+    }
+
+    #[test]
+    fn highlighted_text() {
+        let mut doc = Document::new(DocumentClass::Article);
+        let mut para = Paragraph::new();
+        para.push_text("Check ")
+            .push(Highlight("50% of".to_string()))
+            .push_text(" these");
+        doc.push(para);
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{soul}\n\\usepackage{xcolor}\n"));
+        assert!(rendered.contains("Check \\hl{50\\% of} these\n"));
+    }
+
+    #[test]
+    fn verb_picks_an_unused_delimiter() {
+        let should_be = "Run \\verb!a | b! now\n";
+        let mut buffer = Vec::new();
+
+        let mut para = Paragraph::new();
+        para.push_text("Run ")
+            .push(Verb("a | b".to_string()))
+            .push_text(" now");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_paragraph(&para).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn verb_without_a_free_delimiter_is_an_error() {
+        let mut buffer = Vec::new();
+        let mut printer = Printer::new(&mut buffer);
+
+        let element = Verb(r#"|!+/=:;@"'~"#.to_string());
+        assert!(printer.visit_paragraph_element(&element).is_err());
+    }
+
+    #[test]
+    fn verb_inside_a_command_falls_back_to_texttt() {
+        let mut buffer = Vec::new();
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            let verb = Box::new(Verb("a_b".to_string()));
+            printer.visit_paragraph_element(&Footnote(verb)).unwrap();
+            let verb = Box::new(Verb("c_d".to_string()));
+            printer
+                .visit_paragraph_element(&Bold(Box::new(Italic(verb))))
+                .unwrap();
+            printer
+                .visit_paragraph_element(&Verb("e_f".to_string()))
+                .unwrap();
+        }
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            r"\footnote{\texttt{a\_b}}\textbf{\textit{\texttt{c\_d}}}\verb|e_f|"
+        );
+    }
+
+    #[test]
+    fn paragraph_with_todo_note() {
+        let mut doc = Document::new(DocumentClass::Article);
+        let mut para = Paragraph::new();
+        para.push_text("Hello World")
+            .push(Todo("Add a citation".to_string()));
+        doc.push(para);
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{todonotes}\n"));
+        assert!(rendered.contains("Hello World\\todo{Add a citation}\n"));
+    }
+
+    #[test]
+    fn hidden_todo_notes_are_not_rendered() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.set_mode(DocumentMode::Final);
+        let mut para = Paragraph::new();
+        para.push_text("Hello World")
+            .push(Todo("Add a citation".to_string()));
+        doc.push(para);
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(!rendered.contains("todo"));
+        assert!(rendered.contains("Hello World\n"));
+    }
+
+    #[test]
+    fn different_margins_for_a_few_pages() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(Element::NewGeometry("left=1cm,right=1cm".to_string()))
+            .push("A wide table.")
+            .push(Element::RestoreGeometry);
+
+        let rendered = print(&doc).unwrap();
+
+        let should_be = r"\newgeometry{left=1cm,right=1cm}
+A wide table.
+\restoregeometry
+";
+        assert!(rendered.contains("\\usepackage{geometry}\n"));
+        assert!(rendered.contains(should_be));
+    }
+
+    #[test]
+    fn pdf_comments_are_kept_with_hidden_todos() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.set_mode(DocumentMode::Final);
+        let mut para = Paragraph::new();
+        para.push_text("Hello World")
+            .push(PdfComment("Is this too informal?".to_string()));
+        doc.push(para);
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{pdfcomment}\n"));
+        assert!(rendered.contains("Hello World\\pdfcomment{Is this too informal?}\n"));
+    }
+
+    #[test]
+    fn page_break_before_each_top_level_section() {
+        let mut first = Section::new("First");
+        first.push(Section::new("Nested"));
+        let mut doc = Document::new(DocumentClass::Part);
+        doc.push("Intro.").push(first).push(Section::new("Second"));
+        doc.section_page_break = true;
+
+        let should_be = r"Intro.
+\clearpage
+\section{First}
+
+\subsection{Nested}
+
+\clearpage
+\section{Second}
+";
+        assert_eq!(print(&doc).unwrap(), should_be);
+    }
+
+    #[test]
+    fn line_numbers_for_review() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push("Hello World");
+        assert!(!print(&doc).unwrap().contains("lineno"));
+
+        doc.line_numbers = true;
+        let rendered = print(&doc).unwrap();
+        assert!(rendered.contains("\\usepackage{lineno}\n"));
+        assert!(rendered.contains("\\begin{document}\n\\linenumbers\nHello World\n"));
+    }
+
+    #[test]
+    fn nomenclature_with_one_symbol() {
+        let mut para = Paragraph::new();
+        para.push_text("Light travels at $c$.").push(Nomenclature {
+            symbol: "$c$".to_string(),
+            description: "Speed of light".to_string(),
+        });
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(para).push(Element::PrintNomenclature);
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{nomencl}\n\\makenomenclature\n"));
+        assert!(rendered.contains(
+            "Light travels at $c$.\\nomenclature{$c$}{Speed of light}\n\\printnomenclature\n"
+        ));
+    }
+
+    #[test]
+    fn list_of_shorthands() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.preamble
+            .biblatex(BiblatexOptions::default(), "cases.bib");
+        doc.push(Element::PrintShorthands);
+
+        let rendered = print(&doc).unwrap();
+        assert!(rendered.contains("\\begin{document}\n\\printshorthands\n\\end{document}\n"));
+    }
+
+    #[test]
+    fn footnotes_become_endnotes_in_endnote_mode() {
+        let mut doc = Document::new(DocumentClass::Article);
+        let mut para = Paragraph::new();
+        para.push_text("Hello World")
+            .push(ParagraphElement::footnote("A note."));
+        doc.push(para).push(Element::PrintEndnotes);
+
+        let rendered = print(&doc).unwrap();
+        assert!(rendered.contains("Hello World\\footnote{A note.}\n"));
+        assert!(!rendered.contains("\\usepackage{endnotes}"));
+
+        doc.endnotes = true;
+        let rendered = print(&doc).unwrap();
+        assert!(rendered.contains("\\usepackage{endnotes}\n"));
+        assert!(rendered.contains("Hello World\\endnote{A note.}\n\\theendnotes\n"));
+    }
+
+    #[test]
+    fn paragraph_with_si_units() {
+        let should_be = "g = \\SI{9.81}{\\meter\\per\\second\\squared}\n";
+        let mut buffer = Vec::new();
+
+        let mut para = Paragraph::new();
+        para.push_text("g = ")
+            .push(ParagraphElement::si("9.81", r"\meter\per\second\squared").unwrap());
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_paragraph(&para).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn si_values_must_be_finite_numbers() {
+        let mut buffer = Vec::new();
+        let mut printer = Printer::new(&mut buffer);
+
+        for value in &["inf", "NaN", "infinity", "fast"] {
+            let element = SiUnit {
+                value: value.to_string(),
+                unit: r"\meter".to_string(),
+            };
+            assert!(printer.visit_paragraph_element(&element).is_err());
+            assert!(ParagraphElement::si(value, r"\meter").is_err());
+        }
+    }
+
+    #[test]
     fn preamble_with_author_and_title() {
         let should_be = r#"\title{Sample Document}
 \author{Michael-F-Bryan}
 "#;
         let mut buffer = Vec::new();
 
-        let mut preamble = Preamble::default();
-        preamble.title("Sample Document").author("Michael-F-Bryan");
+        let mut preamble = Preamble::default();
+        preamble.title("Sample Document").author("Michael-F-Bryan");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn author_with_a_thanks_note() {
+        let should_be = "\\author{Ada Lovelace\\thanks{University of London}}\n";
+        let mut buffer = Vec::new();
+
+        let mut preamble = Preamble::default();
+        preamble.author_with_thanks("Ada Lovelace", "University of London");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_a_formatted_date() {
+        let should_be = r"\usepackage{datetime2}
+
+\date{\DTMdisplaydate{2020}{3}{14}{-1}}
+";
+        let mut buffer = Vec::new();
+
+        let mut preamble = Preamble::default();
+        preamble.date_formatted(2020, 3, 14);
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_title_and_package_imports() {
+        let should_be = r#"\usepackage{amsmath}
+\usepackage{graphics}
+
+\title{Sample Document}
+"#;
+        let mut buffer = Vec::new();
+
+        let mut preamble = Preamble::default();
+        preamble
+            .title("Sample Document")
+            .use_package("amsmath")
+            .use_package("graphics");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_newcommand() {
+        let should_be = r#"\newcommand{\Love}[2]{
+#1 loves #2
+}
+"#;
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.new_command("Love", 2, "#1 loves #2");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_a_document_command() {
+        let should_be = r#"\usepackage{xparse}
+\NewDocumentCommand{\greet}{O{Hello}m}{#1, #2!}
+"#;
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.new_document_command("greet", "O{Hello}m", "#1, #2!");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn custom_table_of_contents() {
+        let should_be = r#"\usepackage{tocloft}
+\renewcommand{\contentsname}{Contents of This Thesis}
+\renewcommand{\cftsecleader}{\cftdotfill{\cftdotsep}}
+\renewcommand{\cftdotsep}{1.5}
+"#;
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble
+            .toc_title("Contents of This Thesis")
+            .toc_style(TocStyle {
+                dot_separation: Some("1.5".to_string()),
+                section_dots: true,
+            });
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn hypersetup_comes_after_the_packages() {
+        let should_be = r#"\usepackage{amsmath}
+\usepackage{hyperref}
+\hypersetup{colorlinks=true}
+\newcommand{\R}[0]{
+\mathbb{R}
+}
+"#;
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble
+            .hypersetup(HyperSetup {
+                colorlinks: Some(true),
+                ..Default::default()
+            })
+            .use_package("amsmath")
+            .new_command("R", 0, r"\mathbb{R}");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn empty_hypersetup_is_left_out() {
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.hypersetup(HyperSetup::default());
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "\\usepackage{hyperref}\n"
+        );
+    }
+
+    #[test]
+    fn hypersetup_with_bookmark_depth() {
+        let should_be = r#"\usepackage{hyperref}
+\hypersetup{bookmarksdepth=2,pdftitle={A Report}}
+"#;
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.hypersetup(HyperSetup {
+            bookmarks_depth: Some(2),
+            pdf_title: Some("A Report".to_string()),
+            ..Default::default()
+        });
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn minted_setup_with_cachedir() {
+        let should_be = r#"\usepackage[cachedir=_minted]{minted}
+\setminted{style=friendly,linenos}
+"#;
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.use_package("minted").minted_setup(MintedOptions {
+            style: Some("friendly".to_string()),
+            cachedir: Some("_minted".to_string()),
+            linenos: true,
+        });
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_html_color() {
+        let should_be = r#"\usepackage{xcolor}
+\definecolor{brand}{HTML}{003366}
+"#;
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.define_color("brand", "HTML", "003366").unwrap();
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_xspace_command() {
+        let should_be = r#"\usepackage{xspace}
+\newcommand{\etal}[0]{
+et al.\xspace
+}
+"#;
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.new_command_with_xspace("etal", 0, "et al.");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_newcommand_with_default_argument() {
+        let should_be = r#"\newcommand{\Love}[3][likes]{
+#2 #1 #3
+}
+"#;
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.push(PreambleElement::NewCommand {
+            name: String::from("Love"),
+            args_num: Some(3),
+            default_arg: Some(String::from("likes")),
+            definition: String::from("#2 #1 #3"),
+        });
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_new_environments() {
+        let should_be = r#"\newenvironment{boxed}{\begin{center}}{\end{center}}
+\newenvironment{note}[1]{\begin{quote}\textbf{#1:}}{\end{quote}}
+"#;
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble
+            .new_environment("boxed", r"\begin{center}", r"\end{center}", None)
+            .new_environment(
+                "note",
+                r"\begin{quote}\textbf{#1:}",
+                r"\end{quote}",
+                Some(1),
+            );
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_zeroed_paragraph_indent() {
+        let should_be = "\\setlength{\\parindent}{0pt}\n";
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.set_length(r"\parindent", "0pt").unwrap();
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_math_operators() {
+        let should_be = r"\usepackage{amsmath}
+\DeclareMathOperator*{\argmax}{arg\,max}
+\DeclareMathOperator{\sgn}{sgn}
+";
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble
+            .declare_math_operator("argmax", r"arg\,max", true)
+            .declare_math_operator(r"\sgn", "sgn", false);
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_biblatex_setup() {
+        let should_be = r"\usepackage[backend=biber,style=authoryear,sorting=nyt]{biblatex}
+\addbibresource{main.bib}
+";
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.biblatex(
+            BiblatexOptions {
+                backend: Some("biber".to_string()),
+                style: Some("authoryear".to_string()),
+                sorting: Some("nyt".to_string()),
+            },
+            "main.bib",
+        );
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_double_spacing() {
+        let should_be = "\\usepackage{setspace}\n\\doublespacing\n";
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.line_spacing(LineSpacing::Double);
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+        assert_eq!(LineSpacing::Custom(1.25).to_string(), "\\setstretch{1.25}");
+    }
+
+    #[test]
+    fn preamble_with_two_graphics_paths() {
+        let should_be = "\\usepackage{graphicx}\n\\graphicspath{{figures/}{images/plots/}}\n";
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.graphics_path(&["figures", "images/plots/"]);
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_caption_setup() {
+        let should_be = "\\usepackage{caption}\n\\captionsetup{font=small,labelfont=bf}\n";
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.caption_setup(&[("font", "small"), ("labelfont", "bf")]);
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_natbib_options() {
+        let should_be = "\\usepackage[round,authoryear]{natbib}\n";
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.natbib(&["round"]).natbib(&["round", "authoryear"]);
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn invalid_length_register_is_rejected() {
+        let mut preamble = Preamble::default();
+
+        assert!(preamble.set_length("par indent", "0pt").is_err());
+        assert!(preamble.is_empty());
+    }
+
+    #[test]
+    fn preamble_with_fancy_right_header() {
+        let should_be = r#"\usepackage{fancyhdr}
+\pagestyle{fancy}
+\fancyhf{}
+\fancyhead[R]{Chapter One}
+"#;
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.page_style(PageStyle::Fancy(FancyHeaders {
+            rhead: Some("Chapter One".to_string()),
+            ..Default::default()
+        }));
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn hyperref_is_loaded_after_other_packages() {
+        let should_be = r#"\usepackage{graphicx}
+\usepackage{hyperref}
+\usepackage{cleveref}
+"#;
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble
+            .use_package("cleveref")
+            .use_package("hyperref")
+            .use_package("graphicx");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn hyperref_is_loaded_after_required_packages() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.preamble.use_package("hyperref");
+        doc.push(Align::new());
+
+        let rendered = print(&doc).unwrap();
+        let amsmath = rendered.find(r"\usepackage{amsmath}").unwrap();
+        let hyperref = rendered.find(r"\usepackage{hyperref}").unwrap();
+
+        assert!(amsmath < hyperref);
+    }
+
+    #[test]
+    fn cleveref_is_loaded_when_used() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.preamble.use_package("hyperref");
+        let mut para = Paragraph::new();
+        para.push_text("As shown in ")
+            .push(Bold(Box::new(Plain(::cref("sec:intro")))));
+        doc.push(para);
+
+        let rendered = print(&doc).unwrap();
+        let hyperref = rendered.find(r"\usepackage{hyperref}").unwrap();
+        let cleveref = rendered.find(r"\usepackage{cleveref}").unwrap();
+
+        assert!(hyperref < cleveref);
+    }
+
+    #[test]
+    fn hyperref_is_loaded_for_references() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.preamble.use_package("amsmath");
+        doc.push(r"See section \ref{sec:intro}.");
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{amsmath}\n\\usepackage{hyperref}\n"));
+    }
+
+    #[test]
+    fn hyperref_is_loaded_for_autoref_and_nameref() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(format!("As {} shows.", ::auto_reference("fig:plot")).as_str());
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{hyperref}\n"));
+        assert!(rendered.contains("As \\autoref{fig:plot} shows."));
+
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(format!("See {}.", ::name_reference("sec:method")).as_str());
+        assert!(print(&doc).unwrap().contains("\\usepackage{hyperref}\n"));
+    }
+
+    #[test]
+    fn preamble_with_renewcommand() {
+        let should_be = r#"\renewcommand{\figurename}[0]{
+Fig.
+}
+"#;
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.renew_command("figurename", 0, "Fig.");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn preamble_with_includeonly() {
+        let should_be = "\\includeonly{chapters/intro,chapters/method,notes.tex}\n";
+        let mut buffer = Vec::new();
+        let mut preamble = Preamble::default();
+        preamble.include_only(&["chapters/intro.tex", "chapters/method", "notes.tex.tex"]);
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_preamble(&preamble).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn render_empty_document() {
+        let should_be = r#"\documentclass{article}
+\begin{document}
+\end{document}
+"#;
+        let mut buffer = Vec::new();
+
+        let doc = Document::new(DocumentClass::Article);
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_document(&doc).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn render_enumerated_list() {
+        let should_be = "\\begin{enumerate}\n\\end{enumerate}\n";
+        let mut buffer = Vec::new();
+
+        let list = List::new(ListKind::Enumerate);
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_list(&list).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn render_empty_itemize_list() {
+        let should_be = "\\begin{itemize}\n\\end{itemize}\n";
+        let mut buffer = Vec::new();
+
+        let list = List::new(ListKind::Itemize);
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_list(&list).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn render_list_with_items() {
+        let should_be = r"\begin{itemize}
+\item This
+\item is
+\item a
+\item list!
+\end{itemize}
+";
+        let mut buffer = Vec::new();
+
+        let mut list = List::new(ListKind::Itemize);
+        list.push("This").push("is").push("a").push("list!");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_list(&list).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn render_list_with_custom_labels() {
+        let should_be = r"\begin{enumerate}[label={(\alph*)}]
+\item First
+\item Second
+\end{enumerate}
+";
+        let mut buffer = Vec::new();
+
+        let mut list = List::new(ListKind::Enumerate);
+        list.label_format(r"(\alph*)").unwrap();
+        list.push("First").push("Second");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_list(&list).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn render_compact_itemize() {
+        let should_be = r"\begin{itemize}[noitemsep,topsep=0pt]
+\item Tight
+\item Spacing
+\end{itemize}
+";
+        let mut list = List::new(ListKind::Itemize);
+        list.compact(true).push("Tight").push("Spacing");
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(list);
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{enumitem}\n"));
+        assert!(rendered.contains(should_be));
+    }
+
+    #[test]
+    fn resume_numbering_after_a_paragraph() {
+        let mut first = List::new(ListKind::Enumerate);
+        first.push("One").push("Two");
+        let mut second = List::new(ListKind::Enumerate);
+        second.resume(true).push("Three");
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(first).push("An interruption.").push(second);
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{enumitem}\n"));
+        assert!(rendered.contains("\\begin{enumerate}\n\\item One\n"));
+        assert!(rendered.contains("\\begin{enumerate}[resume]\n\\item Three\n"));
+    }
+
+    #[test]
+    fn render_list_items_with_overlays() {
+        let should_be = r"\begin{itemize}
+\item Always shown
+\item<2->{Revealed on the second slide}
+\end{itemize}
+";
+        let mut buffer = Vec::new();
+
+        let mut list = List::new(ListKind::Itemize);
+        list.push("Always shown")
+            .push_with_overlay("Revealed on the second slide", "2-");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_list(&list).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn thesis_title_page() {
+        let should_be = r"\begin{titlepage}
+\centering
+{\Huge\bfseries A Thesis\par}
+\vspace{2em}
+{\large University of Somewhere\par}
+\vspace{2em}
+{\large Supervisor: Prof. B. Advisor\par}
+\end{titlepage}
+";
+        let mut buffer = Vec::new();
+        let title_page = TitlePageBuilder::new("A Thesis")
+            .university("University of Somewhere")
+            .supervisor("Prof. B. Advisor")
+            .spacing("2em")
+            .build();
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_element(&title_page).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn three_keywords() {
+        let mut buffer = Vec::new();
+        let mut keywords = Keywords::new(&["graphs", "colouring", "planarity"]);
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_element(&keywords.build()).unwrap();
+            printer
+                .visit_element(&keywords.environment("IEEEkeywords").build())
+                .unwrap();
+        }
+
+        let should_be = r"\keywords{graphs, colouring, planarity}
+\begin{IEEEkeywords}
+graphs, colouring, planarity
+\end{IEEEkeywords}
+";
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn single_spaced_abstract() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.preamble.line_spacing(LineSpacing::Double);
+        doc.push(
+            Abstract::new("We colour some graphs.")
+                .single_spaced(true)
+                .build(),
+        );
+
+        let rendered = print(&doc).unwrap();
+
+        let should_be = r"\begin{abstract}
+\begin{singlespace}
+We colour some graphs.
+\end{singlespace}
+\end{abstract}
+";
+        assert!(rendered.contains(should_be));
+        assert_eq!(rendered.matches(r"\usepackage{setspace}").count(), 1);
+    }
+
+    #[test]
+    fn table_with_two_notes() {
+        let table = ThreePartTable {
+            tabular: "\\begin{tabular}{lr}\nMass & 12\\tnote{a}\\\\\n\\end{tabular}".to_string(),
+            notes: vec![
+                "[a] Measured dry.".to_string(),
+                "Source: the lab.".to_string(),
+            ],
+        };
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(table);
+
+        let rendered = print(&doc).unwrap();
+
+        let should_be = r"\begin{threeparttable}
+\begin{tabular}{lr}
+Mass & 12\tnote{a}\\
+\end{tabular}
+\begin{tablenotes}
+\item[a] Measured dry.
+\item Source: the lab.
+\end{tablenotes}
+\end{threeparttable}
+";
+        assert!(rendered.contains("\\usepackage{threeparttable}\n"));
+        assert!(rendered.contains(should_be));
+    }
+
+    #[test]
+    fn empty_style_for_this_page() {
+        let should_be = "\\maketitle\n\\thispagestyle{empty}\n";
+        let mut buffer = Vec::new();
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_element(&Element::TitlePage).unwrap();
+            printer
+                .visit_element(&Element::ThisPageStyle(PageStyle::Empty))
+                .unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn lipsum_paragraph_range() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(Element::Lipsum(Some(1..=3)))
+            .push(Element::Lipsum(Some(5..=5)))
+            .push(Element::Lipsum(None));
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{lipsum}\n"));
+        assert!(rendered.contains("\\lipsum[1-3]\n\\lipsum[5]\n\\lipsum\n"));
+
+        doc.push(Element::Lipsum(Some(RangeInclusive::new(3, 1))));
+        assert!(print(&doc).is_err());
+    }
+
+    #[test]
+    fn include_a_range_of_pdf_pages() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(Element::IncludePdf {
+            path: "appendix.pdf".into(),
+            pages: Some(1..=3),
+        })
+        .push(Element::IncludePdf {
+            path: "forms.pdf".into(),
+            pages: None,
+        });
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{pdfpages}\n"));
+        assert!(rendered.contains(
+            "\\includepdf[pages={1-3}]{appendix.pdf}\n\\includepdf[pages=-]{forms.pdf}\n"
+        ));
+
+        doc.push(Element::IncludePdf {
+            path: "appendix.pdf".into(),
+            pages: Some(RangeInclusive::new(3, 1)),
+        });
+        assert!(print(&doc).is_err());
+    }
+
+    #[test]
+    fn csv_table_with_two_columns() {
+        let should_be = concat!(
+            r"\csvreader[tabular=ll,table head=Name & Score\\]{results.csv}",
+            r"{}{\csvcoli & \csvcolii}",
+            "\n"
+        );
+        let mut table = CsvTable::new("results.csv");
+        table.column("Name").column("Score");
+
+        let mut buffer = Vec::new();
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_csv_table(&table).unwrap();
+        }
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(table);
+        assert!(print(&doc).unwrap().contains("\\usepackage{csvsimple}\n"));
+    }
+
+    #[test]
+    fn switch_to_arabic_page_numbers() {
+        let should_be = "\\pagenumbering{roman}\n\\tableofcontents\n\\pagenumbering{arabic}\n";
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(Element::PageNumbering(NumberStyle::Roman))
+            .push(Element::TableOfContents)
+            .push(Element::PageNumbering(NumberStyle::Arabic));
+
+        let rendered = print_with(
+            &doc,
+            &RenderOptions {
+                body_only: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(rendered, should_be);
+    }
+
+    #[test]
+    fn reset_the_section_counter() {
+        let should_be = "\\setcounter{section}{0}\n\\addtocounter{section}{-1}\n";
+        let mut buffer = Vec::new();
+        let reset = Element::SetCounter {
+            name: "section".to_string(),
+            value: 0,
+        };
+        let step_back = Element::AddToCounter {
+            name: "section".to_string(),
+            delta: -1,
+        };
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_element(&reset).unwrap();
+            printer.visit_element(&step_back).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn render_numbered_chapter() {
+        let should_be = "\\chapter{Introduction}\n";
+        let mut buffer = Vec::new();
+        let chapter = Element::Chapter {
+            title: "Introduction".to_string(),
+            starred: false,
+        };
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_element(&chapter).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn render_unnumbered_part() {
+        let should_be = "\\part*{Appendices}\n";
+        let mut buffer = Vec::new();
+        let part = Element::Part {
+            title: "Appendices".to_string(),
+            starred: true,
+        };
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_element(&part).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn render_blank_section() {
+        let should_be = "\\section{First Section}\n";
+        let mut buffer = Vec::new();
+
+        let section = Section::new("First Section");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_section(&section).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn section_with_paragraphs() {
+        let should_be = r#"\section{First Section}
+
+Lorem Ipsum...
+
+Hello World!
+
+"#;
+        let mut buffer = Vec::new();
+
+        let mut section = Section::new("First Section");
+        section.push("Lorem Ipsum...").push("Hello World!");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_section(&section).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn nested_sections_use_deeper_commands() {
+        let should_be = r#"\section{Outer}
+
+\subsection{Middle}
+
+\subsubsection{Inner}
+
+
+"#;
+        let mut buffer = Vec::new();
+
+        let mut middle = Section::new("Middle");
+        middle.push_section(Section::new("Inner"));
+        let mut outer = Section::new("Outer");
+        outer.push_section(middle);
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_section(&outer).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn multline_with_two_lines() {
+        let should_be = r"\begin{multline}
+\label{eq:sum}
+a + b + c + d \\
++ e + f + g
+\end{multline}
+";
+        let mut equation = LongEquation::new(LongEquationKind::Multline);
+        equation
+            .push("a + b + c + d")
+            .push("+ e + f + g")
+            .label("eq:sum");
+
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(equation);
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{amsmath}\n"));
+        assert!(rendered.contains(should_be));
+    }
+
+    #[test]
+    fn split_inside_an_equation() {
+        let should_be = r"\begin{equation}
+\begin{split}
+x &= (a + b)^2 \\
+&= a^2 + 2ab + b^2
+\end{split}
+\end{equation}
+";
+        let mut equation = LongEquation::new(LongEquationKind::Split);
+        equation.push("x &= (a + b)^2").push("&= a^2 + 2ab + b^2");
+
+        let mut buffer = Vec::new();
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_long_equation(&equation).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn render_empty_align() {
+        let should_be = "\\begin{align}\n\\end{align}\n";
+        let mut buffer = Vec::new();
+
+        let equations = Align::new();
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_align(&equations).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn render_simple_equation() {
+        let should_be = "x &= y + \\sigma \\\\\n";
+        let mut buffer = Vec::new();
+        let eq = Equation::new(r"x &= y + \sigma");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_equation(&eq).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn render_several_equations() {
+        let should_be = r"\begin{align}
+E &= m c^2 \label{eq:mass-energy-equivalence} \\
+y &= m x + c \\
+\end{align}
+";
+        let mut buffer = Vec::new();
+
+        let mut equations = Align::new();
+
+        equations
+            .push(Equation::with_label(
+                "eq:mass-energy-equivalence",
+                "E &= m c^2",
+            ))
+            .push("y &= m x + c");
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_align(&equations).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn align_with_unnumbered_first_row() {
+        let should_be = r"\begin{align}
+f(x) &= (x + 1)^2 \nonumber \\
+&= x^2 + 2x + 1 \\
+\end{align}
+";
+        let mut buffer = Vec::new();
+
+        let mut first = Equation::new("f(x) &= (x + 1)^2");
+        first.not_numbered();
+        let mut equations = Align::new();
+        equations.push(first).push("&= x^2 + 2x + 1");
 
         {
             let mut printer = Printer::new(&mut buffer);
-            printer.visit_preamble(&preamble).unwrap();
+            printer.visit_align(&equations).unwrap();
         }
 
         assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
     }
 
     #[test]
-    fn preamble_with_title_and_package_imports() {
-        let should_be = r#"\usepackage{amsmath}
-\usepackage{graphics}
-
-\title{Sample Document}
-"#;
+    fn equation_with_label() {
+        let should_be = "E &= m c^2 \\label{eq:mass-energy-equivalence} \\\\\n";
         let mut buffer = Vec::new();
 
-        let mut preamble = Preamble::default();
-        preamble
-            .title("Sample Document")
-            .use_package("amsmath")
-            .use_package("graphics");
+        let mut eq = Equation::new("E &= m c^2");
+        eq.label("eq:mass-energy-equivalence");
 
         {
             let mut printer = Printer::new(&mut buffer);
-            printer.visit_preamble(&preamble).unwrap();
+            printer.visit_equation(&eq).unwrap();
         }
 
         assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
     }
 
     #[test]
-    fn preamble_with_newcommand() {
-        let should_be = r#"\newcommand{\Love}[2]{
-#1 loves #2
-}
-"#;
+    fn equation_with_no_numbering() {
+        let should_be = "E &= m c^2 \\nonumber \\\\\n";
         let mut buffer = Vec::new();
-        let mut preamble = Preamble::default();
-        preamble.new_command("Love", 2, "#1 loves #2");
-        
+
+        let mut eq = Equation::new("E &= m c^2");
+        eq.not_numbered();
+
         {
             let mut printer = Printer::new(&mut buffer);
-            printer.visit_preamble(&preamble).unwrap();
+            printer.visit_equation(&eq).unwrap();
         }
 
         assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
     }
 
     #[test]
-    fn preamble_with_newcommand_with_default_argument() {
-        let should_be = r#"\newcommand{\Love}[3][likes]{
-#2 #1 #3
+    fn preview_an_align_block() {
+        let should_be = r"\documentclass{article}
+\usepackage{amsmath}
+\begin{document}
+\begin{align}
+y &= m x + c \\
+\end{align}
+\end{document}
+";
+        let element = Element::Align(Align::from("y &= m x + c"));
+
+        assert_eq!(preview(&element).unwrap(), should_be);
+
+        // the preamble can be left as it is
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(element);
+        let options = RenderOptions {
+            add_required_packages: false,
+            ..Default::default()
+        };
+        assert!(!print_with(&doc, &options).unwrap().contains(r"\usepackage"));
+    }
+
+    #[test]
+    fn required_packages_are_added_after_explicit_ones() {
+        let should_be = r"\documentclass{article}
+\usepackage{graphicx}
+\usepackage{amsmath}
+\newcommand{\R}[0]{
+\mathbb{R}
 }
-"#;
-        let mut buffer = Vec::new();
-        let mut preamble = Preamble::default();
-        preamble.push(
-            PreambleElement::NewCommand {
-                name: String::from("Love"),
-                args_num: Some(3),
-                default_arg: Some(String::from("likes")),
-                definition: String::from("#2 #1 #3")
-            }
+\begin{document}
+\begin{align}
+\end{align}
+\end{document}
+";
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.preamble
+            .use_package("graphicx")
+            .new_command("R", 0, r"\mathbb{R}");
+        doc.push(Align::new());
+
+        assert_eq!(print(&doc).unwrap(), should_be);
+
+        // and the package list agrees with what gets rendered
+        let names: Vec<_> = required_packages(&doc)
+            .unwrap()
+            .into_iter()
+            .map(|package| package.name)
+            .collect();
+        assert_eq!(names, vec!["graphicx", "amsmath"]);
+    }
+
+    #[test]
+    fn document_mode_is_a_class_option() {
+        let mut doc = Document::new(DocumentClass::Report);
+
+        doc.set_mode(DocumentMode::Draft);
+        assert!(print(&doc)
+            .unwrap()
+            .starts_with("\\documentclass[draft]{report}\n"));
+
+        doc.set_mode(DocumentMode::Final);
+        assert!(print(&doc)
+            .unwrap()
+            .starts_with("\\documentclass[final]{report}\n"));
+    }
+
+    #[test]
+    fn hyperref_links_are_turned_off_in_draft_mode() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(r"See section \ref{sec:intro}.");
+
+        doc.set_mode(DocumentMode::Draft);
+        assert!(print(&doc)
+            .unwrap()
+            .contains("\\usepackage[draft]{hyperref}\n"));
+
+        doc.set_mode(DocumentMode::Final);
+        assert!(print(&doc).unwrap().contains("\\usepackage{hyperref}\n"));
+
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.preamble.use_package("hyperref");
+        doc.set_mode(DocumentMode::Draft);
+        assert!(print(&doc)
+            .unwrap()
+            .contains("\\usepackage[draft]{hyperref}\n"));
+    }
+
+    #[test]
+    fn wrapped_body_only_rendering() {
+        let should_be = "The quick brown fox\njumps over the lazy\ndog.\n";
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.preamble.title("Ignored");
+        doc.push("The quick brown fox jumps over the lazy dog.");
+
+        let options = RenderOptions {
+            body_only: true,
+            wrap: Some(20),
+            ..Default::default()
+        };
+
+        assert_eq!(print_with(&doc, &options).unwrap(), should_be);
+    }
+
+    #[test]
+    fn wrapping_keeps_verb_and_comments_intact() {
+        let mut para = Paragraph::new();
+        para.push_text("Run ")
+            .push(Verb("cargo build --release  --all".to_string()))
+            .push_text(r" to build 100\% of it % not everything");
+        let mut doc = Document::new(DocumentClass::Part);
+        doc.push(para).push("Next paragraph.");
+
+        let options = RenderOptions {
+            wrap: Some(10),
+            ..Default::default()
+        };
+
+        let should_be = concat!(
+            "Run\n\\verb|cargo build --release  --all|\n",
+            "to build\n100\\% of\nit\n% not everything\n",
+            "Next\nparagraph.\n"
         );
-        
-        {
-            let mut printer = Printer::new(&mut buffer);
-            printer.visit_preamble(&preamble).unwrap();
-        }
+        assert_eq!(print_with(&doc, &options).unwrap(), should_be);
+    }
 
-        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    #[test]
+    fn tikz_picture_body_is_preserved() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(Element::TikzPicture(r"\draw (0,0) -- (1,1);".to_string()));
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{tikz}\n"));
+        assert!(
+            rendered.contains("\\begin{tikzpicture}\n\\draw (0,0) -- (1,1);\n\\end{tikzpicture}\n")
+        );
     }
 
     #[test]
-    fn render_empty_document() {
-        let should_be = r#"\documentclass{article}
-\begin{document}
-\end{document}
-"#;
+    fn plot_with_three_points() {
+        let should_be = r"\begin{tikzpicture}
+\begin{axis}[xlabel={Time (s)}, ylabel={Distance (m)}]
+\addplot coordinates {(0,0) (1.5,2.25) (3,9)};
+\end{axis}
+\end{tikzpicture}
+";
         let mut buffer = Vec::new();
-
-        let doc = Document::new(DocumentClass::Article);
+        let mut plot = Plot::new(vec![(0.0, 0.0), (1.5, 2.25), (3.0, 9.0)]);
+        plot.x_label("Time (s)").y_label("Distance (m)");
 
         {
             let mut printer = Printer::new(&mut buffer);
-            printer.visit_document(&doc).unwrap();
+            printer.visit_plot(&plot).unwrap();
         }
 
         assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
     }
 
     #[test]
-    fn render_enumerated_list() {
-        let should_be = "\\begin{enumerate}\n\\end{enumerate}\n";
-        let mut buffer = Vec::new();
+    fn preamble_and_body_make_up_the_document() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.preamble.title("Split").use_package("amsmath");
+        let mut section = Section::new("First");
+        section.push(Paragraph::from("Some text."));
+        doc.push(section).push(Element::TableOfContents);
+        doc.line_numbers = true;
+
+        let preamble = doc.preamble_string().unwrap();
+        let body = doc.body_string().unwrap();
+        let joined = format!(
+            "{}\\begin{{document}}\n{}\\end{{document}}\n",
+            preamble, body
+        );
 
-        let list = List::new(ListKind::Enumerate);
+        assert!(preamble.starts_with(r"\documentclass{article}"));
+        assert!(preamble.contains(r"\usepackage{lineno}"));
+        assert!(body.starts_with("\\linenumbers\n"));
+        assert!(!body.contains(r"\usepackage"));
+        assert_eq!(joined, print(&doc).unwrap());
+    }
+
+    #[test]
+    fn proof_with_custom_phrase() {
+        let should_be = r"\begin{proof}[Proof of the main theorem]
+By induction.
+\end{proof}
+";
+        let mut buffer = Vec::new();
+        let mut proof = Proof::with_phrase("Proof of the main theorem");
+        proof.push("By induction.");
 
         {
             let mut printer = Printer::new(&mut buffer);
-            printer.visit_list(&list).unwrap();
+            printer.visit_proof(&proof).unwrap();
         }
 
         assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(proof);
+        assert!(print(&doc).unwrap().contains("\\usepackage{amsthm}\n"));
     }
 
     #[test]
-    fn render_empty_itemize_list() {
-        let should_be = "\\begin{itemize}\n\\end{itemize}\n";
-        let mut buffer = Vec::new();
-
-        let list = List::new(ListKind::Itemize);
+    fn table_in_an_adjustbox() {
+        let should_be = r"\adjustbox{max width=\textwidth}{
+\begin{tabular}
+{ll}
+a & b \\
+\end{tabular}
+}
+";
+        let table = Element::Environment(
+            "tabular".to_string(),
+            vec!["{ll}".to_string(), r"a & b \\".to_string()],
+        );
+        let mut adjust_box = AdjustBox::new(r"\textwidth");
+        adjust_box.push(table);
 
+        let mut buffer = Vec::new();
         {
             let mut printer = Printer::new(&mut buffer);
-            printer.visit_list(&list).unwrap();
+            printer.visit_adjust_box(&adjust_box).unwrap();
         }
-
         assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(adjust_box);
+        assert!(print(&doc).unwrap().contains("\\usepackage{adjustbox}\n"));
     }
 
     #[test]
-    fn render_list_with_items() {
-        let should_be = r"\begin{itemize}
-\item This
-\item is
-\item a
-\item list!
-\end{itemize}
+    fn right_wrapped_figure() {
+        let should_be = r"\begin{wrapfigure}{r}{0.4\textwidth}
+\includegraphics{logo.png}
+\end{wrapfigure}
 ";
-        let mut buffer = Vec::new();
-
-        let mut list = List::new(ListKind::Itemize);
-        list.push("This").push("is").push("a").push("list!");
+        let mut figure = WrapFigure::new(WrapSide::Right, r"0.4\textwidth");
+        figure.push(Element::UserDefined(
+            r"\includegraphics{logo.png}".to_string(),
+        ));
 
+        let mut buffer = Vec::new();
         {
             let mut printer = Printer::new(&mut buffer);
-            printer.visit_list(&list).unwrap();
+            printer.visit_wrap_figure(&figure).unwrap();
         }
-
         assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(figure);
+        assert!(print(&doc).unwrap().contains("\\usepackage{wrapfig}\n"));
     }
 
     #[test]
-    fn render_blank_section() {
-        let should_be = "\\section{First Section}\n";
-        let mut buffer = Vec::new();
-
-        let section = Section::new("First Section");
+    fn titled_color_box() {
+        let should_be = r"\begin{tcolorbox}[title={Warning},colback=red!5]
+Mind the gap.
+\end{tcolorbox}
+";
+        let mut warning = ColorBox::new();
+        warning
+            .title("Warning")
+            .color("red!5")
+            .push("Mind the gap.");
 
+        let mut buffer = Vec::new();
         {
             let mut printer = Printer::new(&mut buffer);
-            printer.visit_section(&section).unwrap();
+            printer.visit_color_box(&warning).unwrap();
         }
-
         assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(warning);
+        assert!(print(&doc).unwrap().contains("\\usepackage{tcolorbox}\n"));
     }
 
     #[test]
-    fn section_with_paragraphs() {
-        let should_be = r#"\section{First Section}
+    fn aligned_blocks() {
+        let alignments = [
+            (Alignment::Centered, "center"),
+            (Alignment::FlushLeft, "flushleft"),
+            (Alignment::FlushRight, "flushright"),
+        ];
+
+        for &(alignment, environment) in &alignments {
+            let mut aligned = Aligned::new(alignment);
+            aligned.push("Some text.");
+
+            let mut buffer = Vec::new();
+            {
+                let mut printer = Printer::new(&mut buffer);
+                printer.visit_element(&aligned.into()).unwrap();
+            }
 
-Lorem Ipsum...
+            let should_be = format!("\\begin{{{0}}}\nSome text.\n\\end{{{0}}}\n", environment);
+            assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+        }
+    }
 
-Hello World!
+    #[test]
+    fn excluded_comment_block() {
+        let should_be = r"\begin{solution}
+The answer is 42.
+\end{solution}
+";
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push("What is six times seven?").push(Element::Comment {
+            name: "solution".to_string(),
+            included: false,
+            content: vec![Element::from("The answer is 42.")],
+        });
 
-"#;
-        let mut buffer = Vec::new();
+        let rendered = print(&doc).unwrap();
 
-        let mut section = Section::new("First Section");
-        section.push("Lorem Ipsum...").push("Hello World!");
+        assert!(rendered.contains("\\usepackage{comment}\n\\excludecomment{solution}\n"));
+        assert!(rendered.contains(should_be));
+    }
+
+    #[test]
+    fn table_in_landscape() {
+        let should_be = r"\begin{landscape}
+\begin{tabular}
+{ll}
+a & b \\
+\end{tabular}
+\end{landscape}
+";
+        let table = Element::Environment(
+            "tabular".to_string(),
+            vec!["{ll}".to_string(), r"a & b \\".to_string()],
+        );
+        let mut landscape = Landscape::new();
+        landscape.push(table);
 
+        let mut buffer = Vec::new();
         {
             let mut printer = Printer::new(&mut buffer);
-            printer.visit_section(&section).unwrap();
+            printer.visit_landscape(&landscape).unwrap();
         }
-
         assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(landscape);
+        assert!(print(&doc).unwrap().contains("\\usepackage{pdflscape}\n"));
     }
 
     #[test]
-    fn render_empty_align() {
-        let should_be = "\\begin{align}\n\\end{align}\n";
+    fn labelled_section() {
+        let should_be = "\\section{Introduction}\n\\label{sec:intro}\n";
         let mut buffer = Vec::new();
-
-        let equations = Align::new();
+        let mut section = Section::new("Introduction");
+        section.label("sec:intro");
 
         {
             let mut printer = Printer::new(&mut buffer);
-            printer.visit_align(&equations).unwrap();
+            printer.visit_section(&section).unwrap();
         }
 
         assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
     }
 
     #[test]
-    fn render_simple_equation() {
-        let should_be = "x &= y + \\sigma \\\\\n";
+    fn automatically_labelled_section() {
+        let should_be = "\\section{Introduction to Rust}\n\\label{sec:introduction-to-rust}\n";
         let mut buffer = Vec::new();
-        let eq = Equation::new(r"x &= y + \sigma");
+        let mut section = Section::new("Introduction to Rust");
+        section.with_auto_label();
+        assert_eq!(section.get_label(), Some("sec:introduction-to-rust"));
+
+        let auto_label = |name| {
+            let mut section = Section::new(name);
+            section.with_auto_label();
+            section.get_label().unwrap().to_string()
+        };
+        assert_eq!(auto_label("Über Straße"), "sec:uber-strasse");
+        assert_eq!(auto_label("§§"), "sec:u00a7-u00a7");
+        assert_eq!(auto_label("  "), "sec:untitled");
 
         {
             let mut printer = Printer::new(&mut buffer);
-            printer.visit_equation(&eq).unwrap();
+            printer.visit_section(&section).unwrap();
         }
 
         assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
     }
 
     #[test]
-    fn render_several_equations() {
-        let should_be = r"\begin{align}
-E &= m c^2 \label{eq:mass-energy-equivalence} \\
-y &= m x + c \\
-\end{align}
+    fn manual_bibliography_with_two_items() {
+        let should_be = r"\begin{thebibliography}{9}
+\bibitem{knuth84} D. E. Knuth, The TeXbook, 1984.
+\bibitem{lamport94} L. Lamport, LaTeX: A Document Preparation System, 1994.
+\end{thebibliography}
 ";
         let mut buffer = Vec::new();
-
-        let mut equations = Align::new();
-
-        equations
-            .push(Equation::with_label(
-                "eq:mass-energy-equivalence",
-                "E &= m c^2",
-            ))
-            .push("y &= m x + c");
+        let mut bib = ManualBibliography::new();
+        bib.push("knuth84", "D. E. Knuth, The TeXbook, 1984.").push(
+            "lamport94",
+            "L. Lamport, LaTeX: A Document Preparation System, 1994.",
+        );
 
         {
             let mut printer = Printer::new(&mut buffer);
-            printer.visit_align(&equations).unwrap();
+            printer.visit_manual_bibliography(&bib).unwrap();
         }
 
         assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
     }
 
     #[test]
-    fn equation_with_label() {
-        let should_be = "E &= m c^2 \\label{eq:mass-energy-equivalence} \\\\\n";
-        let mut buffer = Vec::new();
+    fn define_and_use_an_acronym() {
+        let mut acronyms = AcronymList::new();
+        acronyms.push("API", "API", "Application Programming Interface");
+        let mut para = Paragraph::new();
+        para.push_text("The ")
+            .push(Acro("API".to_string()))
+            .push_text(" is stable.");
+
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push(para).push(acronyms);
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{acronym}\n"));
+        assert!(rendered.contains("The \\ac{API} is stable.\n"));
+        assert!(rendered.contains(
+            r"\begin{acronym}
+\acro{API}[API]{Application Programming Interface}
+\end{acronym}
+"
+        ));
+    }
 
-        let mut eq = Equation::new("E &= m c^2");
-        eq.label("eq:mass-energy-equivalence");
+    #[test]
+    fn renders_with_different_spacing_normalize_equal() {
+        let mut section = Section::new("Results");
+        section.push("It worked.");
+        let mut doc = Document::new(DocumentClass::Part);
+        doc.push("Intro.").push(section);
+
+        let hand_written = "Intro.  \n\\section{Results}\n\n\n\nIt worked.\t\n\n\n";
+        assert_ne!(print(&doc).unwrap(), hand_written);
+        assert_eq!(
+            normalize_latex(&print(&doc).unwrap()),
+            normalize_latex(hand_written)
+        );
+    }
 
-        {
-            let mut printer = Printer::new(&mut buffer);
-            printer.visit_equation(&eq).unwrap();
-        }
+    #[test]
+    fn write_into_an_existing_string() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.preamble.title("Streamed");
+        doc.push("Hello World").push(Section::new("Next"));
 
-        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+        let mut rendered = String::from("% generated\n");
+        write_document(&doc, &mut rendered).unwrap();
+
+        assert_eq!(rendered, format!("% generated\n{}", print(&doc).unwrap()));
     }
 
     #[test]
-    fn equation_with_no_numbering() {
-        let should_be = "E &= m c^2 \\nonumber \\\\\n";
-        let mut buffer = Vec::new();
+    fn render_errors_say_what_went_wrong() {
+        struct Full;
 
-        let mut eq = Equation::new("E &= m c^2");
-        eq.not_numbered();
+        impl fmt::Write for Full {
+            fn write_str(&mut self, _: &str) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
 
-        {
-            let mut printer = Printer::new(&mut buffer);
-            printer.visit_equation(&eq).unwrap();
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push("Hello World");
+        match write_document(&doc, &mut Full) {
+            Err(RenderError::Write(_)) => {}
+            other => panic!("expected a write error, got {:?}", other),
         }
 
+        doc.push(Element::Lipsum(Some(RangeInclusive::new(3, 1))));
+        match print(&doc) {
+            Err(RenderError::Invalid(e)) => assert!(e.to_string().contains("3-1")),
+            other => panic!("expected the document to be invalid, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn raw_text_ending_in_a_comment() {
+        let mut para = Paragraph::new();
+        para.push_text("Total: 50\\% % note")
+            .push_text(" this is still visible");
+        let mut doc = Document::new(DocumentClass::Part);
+        doc.push(para);
+
+        assert_eq!(
+            print(&doc).unwrap(),
+            "Total: 50\\% % note this is still visible\n"
+        );
+
+        let options = RenderOptions {
+            terminate_comments: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            print_with(&doc, &options).unwrap(),
+            "Total: 50\\% % note\n this is still visible\n"
+        );
+
+        // the paragraph's own line ending is enough for a trailing comment
+        let mut doc = Document::new(DocumentClass::Part);
+        doc.push("a % note").push("b");
+        assert_eq!(print_with(&doc, &options).unwrap(), "a % note\nb\n");
+
+        assert!(!ends_in_comment(r"50\% off"));
+        assert!(ends_in_comment(r"a line\\% comment"));
+    }
+
+    #[test]
+    fn windows_line_endings() {
+        let mut doc = Document::new(DocumentClass::Article);
+        doc.push("Hello World")
+            .push(Element::UserDefined("Already\r\nWindows".to_string()));
+        let options = RenderOptions {
+            line_ending: LineEnding::CrLf,
+            ..Default::default()
+        };
+
+        let should_be = "\\documentclass{article}\r\n\\begin{document}\r\nHello World\r\n\
+                         Already\r\nWindows\r\n\\end{document}\r\n";
+        assert_eq!(print_with(&doc, &options).unwrap(), should_be);
+
+        let mut buffer = Vec::new();
+        Printer::with_options(&mut buffer, options)
+            .visit_document(&doc)
+            .unwrap();
         assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
     }
 
@@ -607,4 +3448,78 @@ y &= m x + c \\
         }
         assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
     }
+
+    #[test]
+    fn subfile_and_the_main_document() {
+        let mut chapter = Document::default();
+        chapter.preamble.use_package("ignored");
+        chapter.as_subfile("../main.tex").push("Chapter text.");
+        let should_be = r"\documentclass[../main.tex]{subfiles}
+\begin{document}
+Chapter text.
+\end{document}
+";
+        assert_eq!(print(&chapter).unwrap(), should_be);
+
+        let mut main = Document::new(DocumentClass::Report);
+        main.push(Element::Subfile("chapters/intro".to_string()));
+        let rendered = print(&main).unwrap();
+        assert!(rendered.contains("\\usepackage{subfiles}\n"));
+        assert!(rendered.contains("\\subfile{chapters/intro}\n"));
+    }
+
+    #[test]
+    fn import_from_a_directory() {
+        let mut doc = Document::new(DocumentClass::Report);
+        doc.push(Element::Import {
+            dir: "chapters/".to_string(),
+            file: "intro".to_string(),
+        });
+
+        let rendered = print(&doc).unwrap();
+
+        assert!(rendered.contains("\\usepackage{import}\n"));
+        assert!(rendered.contains("\\import{chapters/}{intro}\n"));
+    }
+
+    /// A path in the temporary directory which no other test (or test run)
+    /// will use.
+    fn unique_temp_path(name: &str) -> PathBuf {
+        let nanos = ::std::time::SystemTime::now()
+            .duration_since(::std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let file = format!("latex-rs-{}-{}-{}.tex", name, ::std::process::id(), nanos);
+        ::std::env::temp_dir().join(file)
+    }
+
+    #[test]
+    fn inline_file_contents() {
+        let path = unique_temp_path("inline-file-contents");
+        fs::write(&path, r"\emph{Hello} from another file").unwrap();
+
+        let should_be = "\\emph{Hello} from another file\n";
+        let mut buffer = Vec::new();
+        let inline = Element::InlineFile(path.clone());
+
+        {
+            let mut printer = Printer::new(&mut buffer);
+            printer.visit_element(&inline).unwrap()
+        }
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+    }
+
+    #[test]
+    fn missing_inline_file_is_an_error() {
+        let path = unique_temp_path("missing");
+        let mut doc = Document::default();
+        doc.push(Element::InlineFile(path.clone()));
+
+        match print(&doc) {
+            Err(RenderError::Io { path: missing, .. }) => assert_eq!(missing, path),
+            other => panic!("expected an IO error, got {:?}", other),
+        }
+    }
 }
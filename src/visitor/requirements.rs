@@ -0,0 +1,374 @@
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use super::Visitor;
+use acronyms::AcronymList;
+use csv_table::CsvTable;
+use document::{Document, DocumentMode, Element, Preamble, PreambleElement};
+use equations::{Align, LongEquation};
+use failure::Error;
+use layout::{AdjustBox, ColorBox, Landscape, WrapFigure};
+use lists::List;
+use paragraph::ParagraphElement;
+use plot::Plot;
+use proof::Proof;
+use section::Section;
+
+/// Commands which may appear in raw text, and the package each one needs. A
+/// command which needs several packages is listed once for each of them.
+pub(crate) const COMMAND_PACKAGES: &[(&str, &str)] = &[
+    (r"\ref{", "hyperref"),
+    (r"\autoref{", "hyperref"),
+    (r"\nameref{", "hyperref"),
+    (r"\href{", "hyperref"),
+    (r"\url{", "hyperref"),
+    (r"\cref{", "hyperref"),
+    (r"\cref{", "cleveref"),
+    (r"\Cref{", "hyperref"),
+    (r"\Cref{", "cleveref"),
+    (r"\includegraphics", "graphicx"),
+    (r"\begin{singlespace}", "setspace"),
+    (r"\begin{pmatrix}", "amsmath"),
+    (r"\begin{bmatrix}", "amsmath"),
+    (r"\begin{vmatrix}", "amsmath"),
+    (r"\begin{cases}", "amsmath"),
+];
+
+/// A `Visitor` which works out which packages (and other preamble lines) the
+/// elements in a document depend on.
+#[derive(Debug, Default)]
+pub struct Requirements {
+    packages: Vec<(String, Option<String>)>,
+    lines: Vec<String>,
+    hide_todos: bool,
+    url_package: bool,
+    draft: bool,
+}
+
+impl Requirements {
+    /// Walk a document and collect everything its elements require.
+    pub fn collect(doc: &Document) -> Result<Requirements, Error> {
+        let mut requirements = Requirements {
+            hide_todos: doc.todos_hidden(),
+            url_package: doc.url_package,
+            draft: doc.mode() == Some(&DocumentMode::Draft),
+            ..Default::default()
+        };
+        if doc.endnotes {
+            requirements.require("endnotes");
+        }
+        if doc.line_numbers {
+            requirements.require("lineno");
+        }
+        requirements.visit_document(doc)?;
+        Ok(requirements)
+    }
+
+    /// Record that a package is required.
+    fn require(&mut self, package: &str) {
+        self.require_with(package, None);
+    }
+
+    /// Record that a package is required, and should be loaded with the
+    /// provided options. If something else already needs the package, the
+    /// options are added to the ones it asked for.
+    fn require_with(&mut self, package: &str, options: Option<&str>) {
+        let existing = self.packages.iter_mut().find(|(p, _)| p == package);
+
+        match (existing, options) {
+            (Some((_, Some(existing))), Some(options)) => {
+                for option in options.split(',') {
+                    if !existing.split(',').any(|o| o == option) {
+                        existing.push(',');
+                        existing.push_str(option);
+                    }
+                }
+            }
+            (Some((_, existing)), Some(options)) => *existing = Some(options.to_string()),
+            (Some(_), None) => {}
+            (None, options) => {
+                let options = options.map(ToString::to_string);
+                self.packages.push((package.to_string(), options));
+            }
+        }
+    }
+
+    /// Record a line which needs to go in the preamble, after the packages.
+    fn require_line(&mut self, line: String) {
+        if !self.lines.contains(&line) {
+            self.lines.push(line);
+        }
+    }
+
+    /// Record that the `nomencl` package is required, along with the
+    /// `\makenomenclature` which sets it up.
+    fn require_nomenclature(&mut self) {
+        self.require("nomencl");
+        self.require_line(r"\makenomenclature".to_string());
+    }
+
+    /// Look through some raw text for any commands which need a package.
+    fn scan(&mut self, text: &str) {
+        for &(command, package) in COMMAND_PACKAGES {
+            if !text.contains(command) {
+                continue;
+            }
+
+            // in url_package mode nothing pulls in hyperref, and validate()
+            // reports any commands which need it
+            if command == r"\url{" && self.url_package {
+                self.require("url");
+            } else if package != "hyperref" || !self.url_package {
+                self.require(package);
+            }
+        }
+    }
+
+    /// Add any required packages which aren't already imported, and any
+    /// required lines which aren't already there, to a copy of the document's
+    /// preamble. Packages which are already imported get any options they
+    /// were required with added to their import.
+    pub fn apply_to(&self, preamble: &Preamble) -> Preamble {
+        let mut preamble = preamble.clone();
+
+        for (package, options) in &self.packages {
+            if !preamble.uses_package(package) {
+                preamble.insert_package(package, options.as_deref());
+            } else if let Some(options) = options {
+                for option in options.split(',') {
+                    preamble.add_package_option(package, option);
+                }
+            }
+        }
+
+        // hyperref's draft option turns off links, which speeds up drafts
+        if self.draft {
+            preamble.add_package_option("hyperref", "draft");
+        }
+
+        for line in &self.lines {
+            let element = PreambleElement::UserDefined(line.clone());
+            if !preamble.iter().any(|existing| *existing == element) {
+                preamble.push(element);
+            }
+        }
+
+        preamble
+    }
+}
+
+impl Visitor for Requirements {
+    fn visit_paragraph_element(&mut self, element: &ParagraphElement) -> Result<(), Error> {
+        match *element {
+            ParagraphElement::Plain(ref s) => self.scan(s),
+            ParagraphElement::Bold(ref e) | ParagraphElement::Italic(ref e) => {
+                self.visit_paragraph_element(e)?
+            }
+            ParagraphElement::Footnote(ref e) => self.visit_paragraph_element(e)?,
+            ParagraphElement::Enquote(_) => self.require("csquotes"),
+            ParagraphElement::Todo(_) => {
+                if !self.hide_todos {
+                    self.require("todonotes");
+                }
+            }
+            ParagraphElement::PdfComment(_) => self.require("pdfcomment"),
+            ParagraphElement::SiUnit { .. } => self.require("siunitx"),
+            ParagraphElement::Nomenclature { .. } => self.require_nomenclature(),
+            // normalem stops ulem from turning \emph into an underline
+            ParagraphElement::Underline(_) | ParagraphElement::Strikeout(_) => {
+                self.require_with("ulem", Some("normalem"))
+            }
+            // soul's \hl needs xcolor for the highlight colour
+            ParagraphElement::Highlight(_) => {
+                self.require("soul");
+                self.require("xcolor");
+            }
+            ParagraphElement::InlineMath(ref s) => self.scan(s),
+            ParagraphElement::Acro(_) => self.require("acronym"),
+            ParagraphElement::Code(_) | ParagraphElement::Verb(_) => {}
+        }
+
+        Ok(())
+    }
+
+    fn visit_user_defined_line(&mut self, line: &str) -> Result<(), Error> {
+        self.scan(line);
+        Ok(())
+    }
+
+    fn visit_custom_environment<'a, I>(&mut self, name: &str, lines: I) -> Result<(), Error>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        match name {
+            "lstlisting" => self.require("listings"),
+            "threeparttable" => self.require("threeparttable"),
+            _ => {}
+        }
+        for line in lines {
+            self.scan(line);
+        }
+
+        Ok(())
+    }
+
+    fn visit_list(&mut self, list: &List) -> Result<(), Error> {
+        if !list.options().is_empty() {
+            self.require("enumitem");
+        }
+        for item in list.iter() {
+            self.scan(item);
+        }
+
+        Ok(())
+    }
+
+    fn visit_section(&mut self, section: &Section) -> Result<(), Error> {
+        for (package, options) in section.required_packages() {
+            self.require_with(package, options);
+        }
+        for element in section.iter() {
+            self.visit_element(element)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_subfile(&mut self, _: &str) -> Result<(), Error> {
+        self.require("subfiles");
+        Ok(())
+    }
+
+    fn visit_import(&mut self, _: &str, _: &str) -> Result<(), Error> {
+        self.require("import");
+        Ok(())
+    }
+
+    fn visit_tikz_picture(&mut self, _: &str) -> Result<(), Error> {
+        self.require("tikz");
+        Ok(())
+    }
+
+    fn visit_plot(&mut self, _: &Plot) -> Result<(), Error> {
+        self.require("pgfplots");
+        Ok(())
+    }
+
+    fn visit_csv_table(&mut self, _: &CsvTable) -> Result<(), Error> {
+        self.require("csvsimple");
+        Ok(())
+    }
+
+    fn visit_proof(&mut self, proof: &Proof) -> Result<(), Error> {
+        self.require("amsthm");
+        for element in proof.iter() {
+            self.visit_element(element)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_landscape(&mut self, landscape: &Landscape) -> Result<(), Error> {
+        self.require("pdflscape");
+        for element in landscape.iter() {
+            self.visit_element(element)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_adjust_box(&mut self, adjust_box: &AdjustBox) -> Result<(), Error> {
+        self.require("adjustbox");
+        for element in adjust_box.iter() {
+            self.visit_element(element)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_wrap_figure(&mut self, figure: &WrapFigure) -> Result<(), Error> {
+        self.require("wrapfig");
+        for element in figure.iter() {
+            self.visit_element(element)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_color_box(&mut self, color_box: &ColorBox) -> Result<(), Error> {
+        self.require("tcolorbox");
+        for element in color_box.iter() {
+            self.visit_element(element)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_comment(
+        &mut self,
+        name: &str,
+        included: bool,
+        content: &[Element],
+    ) -> Result<(), Error> {
+        self.require("comment");
+        let command = if included {
+            "includecomment"
+        } else {
+            "excludecomment"
+        };
+        self.require_line(format!(r"\{}{{{}}}", command, name));
+        for element in content {
+            self.visit_element(element)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_lipsum(&mut self, _: Option<&RangeInclusive<usize>>) -> Result<(), Error> {
+        self.require("lipsum");
+        Ok(())
+    }
+
+    fn visit_acronyms(&mut self, _: &AcronymList) -> Result<(), Error> {
+        self.require("acronym");
+        Ok(())
+    }
+
+    fn visit_include_pdf(
+        &mut self,
+        _: &Path,
+        _: Option<&RangeInclusive<usize>>,
+    ) -> Result<(), Error> {
+        self.require("pdfpages");
+        Ok(())
+    }
+
+    fn visit_print_nomenclature(&mut self) -> Result<(), Error> {
+        self.require_nomenclature();
+        Ok(())
+    }
+
+    fn visit_new_geometry(&mut self, _: &str) -> Result<(), Error> {
+        self.require("geometry");
+        Ok(())
+    }
+
+    fn visit_restore_geometry(&mut self) -> Result<(), Error> {
+        self.require("geometry");
+        Ok(())
+    }
+
+    fn visit_align(&mut self, _: &Align) -> Result<(), Error> {
+        self.require("amsmath");
+        Ok(())
+    }
+
+    fn visit_long_equation(&mut self, equation: &LongEquation) -> Result<(), Error> {
+        self.require("amsmath");
+        for line in equation.iter() {
+            self.scan(line);
+        }
+
+        Ok(())
+    }
+}
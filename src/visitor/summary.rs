@@ -0,0 +1,109 @@
+use super::Visitor;
+use csv_table::CsvTable;
+use document::Document;
+use equations::{Equation, LongEquation};
+use failure::Error;
+use layout::WrapFigure;
+use section::Section;
+
+/// How many of each kind of element a document contains, as counted by
+/// `Document::element_summary()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ElementSummary {
+    /// Sections at any depth.
+    pub sections: usize,
+    /// `figure` environments and `WrapFigure`s.
+    pub figures: usize,
+    /// `table` environments, `threeparttable`s and `CsvTable`s.
+    pub tables: usize,
+    /// Equations, counting each line of an `Align` separately.
+    pub equations: usize,
+}
+
+/// Count the elements in a document, including ones nested inside sections
+/// and other elements.
+pub(crate) fn element_summary(doc: &Document) -> ElementSummary {
+    let mut summary = ElementSummary::default();
+
+    for element in doc.iter() {
+        summary
+            .visit_element(element)
+            .expect("summarising a document never fails");
+    }
+
+    summary
+}
+
+impl Visitor for ElementSummary {
+    fn visit_section(&mut self, section: &Section) -> Result<(), Error> {
+        self.sections += 1;
+        for element in section.iter() {
+            self.visit_element(element)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_wrap_figure(&mut self, figure: &WrapFigure) -> Result<(), Error> {
+        self.figures += 1;
+        for element in figure.iter() {
+            self.visit_element(element)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_csv_table(&mut self, _: &CsvTable) -> Result<(), Error> {
+        self.tables += 1;
+        Ok(())
+    }
+
+    fn visit_equation(&mut self, _: &Equation) -> Result<(), Error> {
+        self.equations += 1;
+        Ok(())
+    }
+
+    fn visit_long_equation(&mut self, _: &LongEquation) -> Result<(), Error> {
+        self.equations += 1;
+        Ok(())
+    }
+
+    fn visit_custom_environment<'a, I>(&mut self, name: &str, _: I) -> Result<(), Error>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        match name {
+            "figure" | "figure*" => self.figures += 1,
+            "table" | "table*" | "threeparttable" => self.tables += 1,
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use document::Element;
+
+    #[test]
+    fn two_sections_and_a_figure() {
+        let figure = Element::Environment(
+            "figure".to_string(),
+            vec![r"\includegraphics{plot.png}".to_string()],
+        );
+        let mut method = Section::new("Method");
+        method.push(Section::new("Apparatus")).push(figure);
+
+        let mut doc = Document::default();
+        doc.push("Intro.").push(method);
+
+        let should_be = ElementSummary {
+            sections: 2,
+            figures: 1,
+            ..Default::default()
+        };
+        assert_eq!(doc.element_summary(), should_be);
+    }
+}
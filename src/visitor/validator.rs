@@ -0,0 +1,657 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use std::mem;
+
+use super::printer::required_packages;
+use super::requirements::COMMAND_PACKAGES;
+use super::Visitor;
+use bibliography::{self, BibBackend, BibStyle, ManualBibliography};
+use document::{Document, Element, Engine};
+use equations::{Equation, LongEquation};
+use failure::Error;
+use lists::Item;
+use paragraph::ParagraphElement;
+use section::Section;
+
+/// Pairs of packages which can't be loaded together.
+const CONFLICTING_PACKAGES: &[(&str, &str)] = &[
+    ("subfigure", "subcaption"),
+    ("subfig", "subcaption"),
+    ("subfigure", "subfig"),
+    ("fancyhdr", "scrlayer-scrpage"),
+    ("natbib", "biblatex"),
+];
+
+/// Environments whose contents are copied out as they are, so any `\begin`,
+/// `\end` or `%` inside them doesn't mean anything to LaTeX.
+const VERBATIM_ENVIRONMENTS: &[&str] = &[
+    "verbatim",
+    "verbatim*",
+    "Verbatim",
+    "lstlisting",
+    "minted",
+    "comment",
+];
+
+/// Packages which only work with `xelatex` or `lualatex`.
+const UNICODE_ENGINE_PACKAGES: &[&str] = &["fontspec", "unicode-math"];
+
+/// Check a document for likely mistakes which LaTeX would either reject or
+/// silently get wrong.
+///
+/// # Examples
+///
+/// ```rust
+/// use latex::{classic_commands, validate, BibStyle, Diagnostic, Document, Element};
+///
+/// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut doc = Document::default();
+/// let style = BibStyle::Custom("palin".to_string());
+/// doc.push(Element::UserDefined(classic_commands(&style, "refs")));
+///
+/// let problems = validate(&doc)?;
+/// assert_eq!(problems, vec![Diagnostic::UnknownBibStyle("palin".to_string())]);
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// # run().unwrap();
+/// # }
+/// ```
+///
+/// If the document has a `bib_backend`, using the other backend's commands is
+/// an error rather than a `Diagnostic`, because LaTeX can't produce a
+/// bibliography at all when they're mixed.
+pub fn validate(doc: &Document) -> Result<Vec<Diagnostic>, Error> {
+    if doc.bib_backend == Some(BibBackend::Bibtex) && doc.preamble.uses_package("biblatex") {
+        bail!(
+            "The document uses the BibTeX backend, but loads the biblatex package. \
+             Remove the package or switch to the biblatex backend"
+        );
+    }
+
+    let mut validator = Validator {
+        bib_backend: doc.bib_backend,
+        check_label_prefixes: doc.check_label_prefixes,
+        url_package: doc.url_package && !doc.preamble.uses_package("hyperref"),
+        ..Default::default()
+    };
+    validator.visit_document(doc)?;
+    let unclosed = mem::take(&mut validator.open_environments);
+    validator.diagnostics.extend(
+        unclosed
+            .into_iter()
+            .chain(validator.open_verbatim.take())
+            .map(Diagnostic::UnbalancedEnvironment),
+    );
+
+    let packages: Vec<_> = required_packages(doc)?
+        .into_iter()
+        .map(|package| package.name)
+        .collect();
+    for &(first, second) in CONFLICTING_PACKAGES {
+        if packages.iter().any(|p| p == first) && packages.iter().any(|p| p == second) {
+            validator
+                .diagnostics
+                .push(Diagnostic::ConflictingPackages(first, second));
+        }
+    }
+
+    if doc.engine == Some(Engine::Pdflatex) {
+        for &package in UNICODE_ENGINE_PACKAGES {
+            if packages.iter().any(|p| p == package) {
+                validator
+                    .diagnostics
+                    .push(Diagnostic::UnsupportedByEngine(package, Engine::Pdflatex));
+            }
+        }
+    }
+
+    Ok(validator.diagnostics)
+}
+
+/// A problem found when validating a document.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Diagnostic {
+    /// A `\bibliographystyle` which isn't one of the well-known BibTeX styles.
+    UnknownBibStyle(String),
+    /// A label which doesn't start with the conventional prefix for the kind
+    /// of element it labels. Only produced when a document's
+    /// `check_label_prefixes` is set.
+    UnexpectedLabelPrefix {
+        /// The offending label.
+        label: String,
+        /// The prefix it should have started with (e.g. `sec:`).
+        expected: &'static str,
+    },
+    /// The same label is given to more than one element, so references to
+    /// it will point at whichever LaTeX sees last.
+    DuplicateLabel(String),
+    /// Two packages which are known not to work together are both loaded.
+    ConflictingPackages(&'static str, &'static str),
+    /// A command which needs `hyperref` is used in a document with
+    /// `url_package` set, so `hyperref` isn't loaded for it.
+    NeedsHyperref(&'static str),
+    /// A `\begin` in some raw text without a matching `\end`, or the other
+    /// way around.
+    UnbalancedEnvironment(String),
+    /// A package is loaded which doesn't work with the document's `engine`.
+    UnsupportedByEngine(&'static str, Engine),
+    /// Some `Element::Comment` blocks with the same name are included and
+    /// others are excluded, but the `comment` package can only do one or the
+    /// other for each name.
+    InconsistentComment(String),
+
+    // Add a dummy variant so we can expand later on without breaking stuff
+    #[doc(hidden)]
+    _Other,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Diagnostic::UnknownBibStyle(ref name) => {
+                write!(f, "\"{}\" is not a known bibliography style", name)
+            }
+            Diagnostic::UnexpectedLabelPrefix {
+                ref label,
+                expected,
+            } => write!(
+                f,
+                "the label \"{}\" should start with \"{}\"",
+                label, expected
+            ),
+            Diagnostic::DuplicateLabel(ref label) => {
+                write!(f, "the label \"{}\" is used more than once", label)
+            }
+            Diagnostic::ConflictingPackages(first, second) => {
+                write!(f, "the {} and {} packages conflict", first, second)
+            }
+            Diagnostic::NeedsHyperref(command) => write!(
+                f,
+                "{}}} needs the hyperref package, which url_package mode doesn't load",
+                command
+            ),
+            Diagnostic::UnbalancedEnvironment(ref name) => {
+                write!(
+                    f,
+                    "the \"{}\" environment isn't opened and closed in pairs",
+                    name
+                )
+            }
+            Diagnostic::UnsupportedByEngine(package, engine) => {
+                write!(f, "the {} package doesn't work with {}", package, engine)
+            }
+            Diagnostic::InconsistentComment(ref name) => write!(
+                f,
+                "the \"{}\" comment blocks are included in some places and excluded in others",
+                name
+            ),
+            Diagnostic::_Other => unreachable!(),
+        }
+    }
+}
+
+/// The `Visitor` which collects `Diagnostic`s for `validate()`.
+#[derive(Debug, Default)]
+struct Validator {
+    diagnostics: Vec<Diagnostic>,
+    bib_backend: Option<BibBackend>,
+    check_label_prefixes: bool,
+    labels: HashSet<String>,
+    comments: HashMap<String, bool>,
+    url_package: bool,
+    open_environments: Vec<String>,
+    open_verbatim: Option<String>,
+}
+
+impl Validator {
+    /// Look through some raw text for problems.
+    fn scan(&mut self, text: &str) -> Result<(), Error> {
+        for name in command_arguments(text, r"\bibliographystyle") {
+            if self.bib_backend == Some(BibBackend::Biblatex) {
+                bail!(
+                    "\\bibliographystyle{{{}}} can't be used with the biblatex backend, \
+                     pass the style as a biblatex package option instead",
+                    name
+                );
+            }
+
+            let style = BibStyle::Custom(name.to_string());
+            self.diagnostics.extend(style.validate());
+        }
+
+        if self.url_package {
+            for &(command, package) in COMMAND_PACKAGES {
+                let diagnostic = Diagnostic::NeedsHyperref(command);
+                if package == "hyperref"
+                    && command != r"\url{"
+                    && text.contains(command)
+                    && !self.diagnostics.contains(&diagnostic)
+                {
+                    self.diagnostics.push(diagnostic);
+                }
+            }
+        }
+
+        self.check_environments(text);
+
+        Ok(())
+    }
+
+    /// Keep track of the environments opened and closed in some raw text,
+    /// which may be split across several elements. Comments and the insides
+    /// of verbatim environments are skipped.
+    fn check_environments(&mut self, text: &str) {
+        for line in text.lines() {
+            let mut rest = line;
+
+            while !rest.is_empty() {
+                rest = match self.open_verbatim.take() {
+                    Some(name) => {
+                        let end = format!(r"\end{{{}}}", name);
+                        match rest.find(&end) {
+                            Some(i) => &rest[i + end.len()..],
+                            None => {
+                                self.open_verbatim = Some(name);
+                                break;
+                            }
+                        }
+                    }
+                    None => match self.check_environment_commands(strip_comment(rest)) {
+                        Some(start) => &rest[start..],
+                        None => break,
+                    },
+                };
+            }
+        }
+    }
+
+    /// Check the `\begin`s and `\end`s in some text which has no comments,
+    /// returning where the contents of a verbatim environment start if one
+    /// is opened.
+    fn check_environment_commands(&mut self, text: &str) -> Option<usize> {
+        let mut commands: Vec<_> = command_arguments_at(text, r"\begin")
+            .into_iter()
+            .map(|(i, name)| (i, true, name))
+            .chain(
+                command_arguments_at(text, r"\end")
+                    .into_iter()
+                    .map(|(i, name)| (i, false, name)),
+            )
+            .collect();
+        commands.sort_by_key(|&(i, _, _)| i);
+
+        for (i, begin, name) in commands {
+            if begin && VERBATIM_ENVIRONMENTS.contains(&name) {
+                self.open_verbatim = Some(name.to_string());
+                return Some(i + r"\begin{".len() + name.len() + 1);
+            } else if begin {
+                self.open_environments.push(name.to_string());
+            } else if let Some(i) = self.open_environments.iter().rposition(|n| n == name) {
+                // anything opened after this environment was never closed
+                for unclosed in self.open_environments.drain(i..).skip(1) {
+                    self.diagnostics
+                        .push(Diagnostic::UnbalancedEnvironment(unclosed));
+                }
+            } else {
+                self.diagnostics
+                    .push(Diagnostic::UnbalancedEnvironment(name.to_string()));
+            }
+        }
+
+        None
+    }
+
+    /// Check that a label hasn't been used already, and that it starts with
+    /// the expected prefix if the document asked for that.
+    fn check_label(&mut self, label: Option<&str>, expected: &'static str) {
+        if let Some(label) = label {
+            // only report each duplicate once
+            let duplicate = Diagnostic::DuplicateLabel(label.to_string());
+            if !self.labels.insert(label.to_string()) && !self.diagnostics.contains(&duplicate) {
+                self.diagnostics.push(duplicate);
+            }
+        }
+
+        match label {
+            Some(label) if self.check_label_prefixes && !label.starts_with(expected) => {
+                self.diagnostics.push(Diagnostic::UnexpectedLabelPrefix {
+                    label: label.to_string(),
+                    expected,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Visitor for Validator {
+    fn visit_paragraph_element(&mut self, element: &ParagraphElement) -> Result<(), Error> {
+        match *element {
+            ParagraphElement::Plain(ref s) => self.scan(s)?,
+            ParagraphElement::Bold(ref e)
+            | ParagraphElement::Italic(ref e)
+            | ParagraphElement::Footnote(ref e) => self.visit_paragraph_element(e)?,
+            ParagraphElement::InlineMath(_)
+            | ParagraphElement::Enquote(_)
+            | ParagraphElement::Acro(_)
+            | ParagraphElement::Code(_)
+            | ParagraphElement::Verb(_)
+            | ParagraphElement::Underline(_)
+            | ParagraphElement::Strikeout(_)
+            | ParagraphElement::Highlight(_)
+            | ParagraphElement::Todo(_)
+            | ParagraphElement::PdfComment(_)
+            | ParagraphElement::SiUnit { .. }
+            | ParagraphElement::Nomenclature { .. } => {}
+        }
+
+        Ok(())
+    }
+
+    fn visit_user_defined_line(&mut self, line: &str) -> Result<(), Error> {
+        self.scan(line)
+    }
+
+    fn visit_section(&mut self, section: &Section) -> Result<(), Error> {
+        self.check_label(section.get_label(), "sec:");
+        for element in section.iter() {
+            self.visit_element(element)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_comment(
+        &mut self,
+        name: &str,
+        included: bool,
+        content: &[Element],
+    ) -> Result<(), Error> {
+        let inconsistent = Diagnostic::InconsistentComment(name.to_string());
+        match self.comments.insert(name.to_string(), included) {
+            Some(previous) if previous != included && !self.diagnostics.contains(&inconsistent) => {
+                self.diagnostics.push(inconsistent);
+            }
+            _ => {}
+        }
+
+        // an excluded block is left out of the output, so its labels can't
+        // clash with anything
+        if included {
+            for element in content {
+                self.visit_element(element)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn visit_manual_bibliography(&mut self, bib: &ManualBibliography) -> Result<(), Error> {
+        for (key, _) in bib.iter() {
+            bibliography::validate_key(key)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_equation(&mut self, equation: &Equation) -> Result<(), Error> {
+        self.check_label(equation.get_label(), "eq:");
+        Ok(())
+    }
+
+    fn visit_long_equation(&mut self, equation: &LongEquation) -> Result<(), Error> {
+        self.check_label(equation.get_label(), "eq:");
+        Ok(())
+    }
+
+    fn visit_list_item(&mut self, item: &Item) -> Result<(), Error> {
+        self.scan(item)
+    }
+
+    fn visit_custom_environment<'a, I>(&mut self, name: &str, lines: I) -> Result<(), Error>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        if VERBATIM_ENVIRONMENTS.contains(&name) {
+            return Ok(());
+        }
+
+        for line in lines {
+            self.scan(line)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Cut off the `%` comment at the end of a line, if it has one.
+fn strip_comment(line: &str) -> &str {
+    let mut escaped = false;
+
+    for (i, c) in line.char_indices() {
+        match c {
+            '%' if !escaped => return &line[..i],
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+
+    line
+}
+
+/// Find the (first) argument passed to each use of a command in some text.
+fn command_arguments<'a>(text: &'a str, command: &str) -> Vec<&'a str> {
+    command_arguments_at(text, command)
+        .into_iter()
+        .map(|(_, argument)| argument)
+        .collect()
+}
+
+/// Like `command_arguments()`, but also gives the position of each use.
+fn command_arguments_at<'a>(text: &'a str, command: &str) -> Vec<(usize, &'a str)> {
+    let pattern = format!("{}{{", command);
+
+    text.match_indices(&pattern)
+        .filter_map(|(start, _)| {
+            let rest = &text[start + pattern.len()..];
+            rest.find('}').map(|end| (start, &rest[..end]))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bibliography::classic_commands;
+    use equations::Align;
+
+    #[test]
+    fn bibtex_style_conflicts_with_biblatex() {
+        let mut doc = Document::default();
+        doc.bib_backend = Some(BibBackend::Biblatex);
+        doc.push(Element::UserDefined(classic_commands(
+            &BibStyle::Plain,
+            "refs",
+        )));
+
+        let err = validate(&doc).unwrap_err();
+        assert!(err.to_string().contains(r"\bibliographystyle{plain}"));
+
+        doc.bib_backend = Some(BibBackend::Bibtex);
+        assert_eq!(validate(&doc).unwrap(), Vec::new());
+
+        doc.preamble.use_package("biblatex");
+        assert!(validate(&doc).is_err());
+    }
+
+    #[test]
+    fn conflicting_packages() {
+        let mut doc = Document::default();
+        doc.preamble
+            .use_package("subfigure")
+            .use_package("graphicx");
+        assert_eq!(validate(&doc).unwrap(), Vec::new());
+
+        doc.preamble.use_package("subcaption");
+        assert_eq!(
+            validate(&doc).unwrap(),
+            vec![Diagnostic::ConflictingPackages("subfigure", "subcaption")]
+        );
+    }
+
+    #[test]
+    fn fontspec_needs_a_unicode_engine() {
+        let mut doc = Document::default();
+        doc.preamble.main_font("TeX Gyre Pagella");
+        assert_eq!(validate(&doc).unwrap(), Vec::new());
+
+        doc.engine = Some(Engine::Xelatex);
+        assert_eq!(validate(&doc).unwrap(), Vec::new());
+
+        doc.engine = Some(Engine::Pdflatex);
+        assert_eq!(
+            validate(&doc).unwrap(),
+            vec![Diagnostic::UnsupportedByEngine(
+                "fontspec",
+                Engine::Pdflatex
+            )]
+        );
+    }
+
+    #[test]
+    fn unbalanced_environments() {
+        let mut doc = Document::default();
+        doc.push(Element::UserDefined(r"\begin{center}".to_string()))
+            .push("Centred text.")
+            .push(Element::UserDefined(r"\end{center}".to_string()));
+        assert_eq!(validate(&doc).unwrap(), Vec::new());
+
+        doc.push(Element::UserDefined(
+            r"\begin{foo}\begin{bar}\end{bar}".to_string(),
+        ));
+        assert_eq!(
+            validate(&doc).unwrap(),
+            vec![Diagnostic::UnbalancedEnvironment("foo".to_string())]
+        );
+    }
+
+    #[test]
+    fn verbatim_and_comments_are_not_checked_for_environments() {
+        let mut doc = Document::default();
+        doc.push(Element::UserDefined(
+            "\\begin{verbatim}\n\\begin{itemize}\n\\end{verbatim} % \\end{center}".to_string(),
+        ))
+        .push(Element::Environment(
+            "lstlisting".to_string(),
+            vec![r"\end{document}".to_string()],
+        ))
+        .push(Element::UserDefined(
+            r"50\% done \begin{center}\end{center}".to_string(),
+        ));
+        assert_eq!(validate(&doc).unwrap(), Vec::new());
+
+        doc.push(Element::UserDefined(r"\begin{lstlisting}".to_string()));
+        assert_eq!(
+            validate(&doc).unwrap(),
+            vec![Diagnostic::UnbalancedEnvironment("lstlisting".to_string())]
+        );
+    }
+
+    #[test]
+    fn invalid_bibliography_keys() {
+        let mut bib = ManualBibliography::new();
+        bib.push("akey2020", "Fine.");
+        let mut doc = Document::default();
+        doc.push(bib.clone());
+        assert!(validate(&doc).is_ok());
+
+        bib.push("a key", "Broken.");
+        let mut doc = Document::default();
+        doc.push(bib);
+        let err = validate(&doc).unwrap_err();
+        assert!(err.to_string().contains("\"a key\""));
+    }
+
+    #[test]
+    fn duplicate_labels() {
+        let mut first = Section::new("Method");
+        first.label("sec:x");
+        let mut second = Section::new("Results");
+        second.label("sec:x");
+        let mut equations = Align::new();
+        equations
+            .push(Equation::with_label("eq:x", "a &= b"))
+            .push(Equation::with_label("sec:x", "c &= d"));
+        second.push(Element::Align(equations));
+
+        let mut doc = Document::default();
+        doc.push(first).push(second);
+
+        assert_eq!(
+            validate(&doc).unwrap(),
+            vec![Diagnostic::DuplicateLabel("sec:x".to_string())]
+        );
+
+        // only one of these blocks is ever in the output
+        let answer = |included| {
+            let mut section = Section::new("Answer");
+            section.label("sec:answer");
+            Element::Comment {
+                name: if included { "long" } else { "short" }.to_string(),
+                included,
+                content: vec![Element::Section(section)],
+            }
+        };
+        let mut doc = Document::default();
+        doc.push(answer(true)).push(answer(false));
+        assert_eq!(validate(&doc).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn comment_blocks_which_are_included_and_excluded() {
+        let solution = |included| Element::Comment {
+            name: "solution".to_string(),
+            included,
+            content: vec![Element::from("x = 2")],
+        };
+
+        let mut doc = Document::default();
+        doc.push(solution(true)).push(solution(true));
+        assert_eq!(validate(&doc).unwrap(), Vec::new());
+
+        doc.push(solution(false)).push(solution(true));
+        assert_eq!(
+            validate(&doc).unwrap(),
+            vec![Diagnostic::InconsistentComment("solution".to_string())]
+        );
+    }
+
+    #[test]
+    fn labels_without_the_expected_prefix() {
+        let mut section = Section::new("Results");
+        section.label("foo");
+        let mut equations = Align::new();
+        equations
+            .push(Equation::with_label("eq:energy", "E &= mc^2"))
+            .push(Equation::with_label("momentum", "p &= mv"));
+        section.push(Element::Align(equations));
+
+        let mut doc = Document::default();
+        doc.push(section);
+        assert_eq!(validate(&doc).unwrap(), Vec::new());
+
+        doc.check_label_prefixes = true;
+        let should_be = vec![
+            Diagnostic::UnexpectedLabelPrefix {
+                label: "foo".to_string(),
+                expected: "sec:",
+            },
+            Diagnostic::UnexpectedLabelPrefix {
+                label: "momentum".to_string(),
+                expected: "eq:",
+            },
+        ];
+        assert_eq!(validate(&doc).unwrap(), should_be);
+    }
+}